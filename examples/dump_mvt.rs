@@ -2,10 +2,10 @@ use std::env;
 use std::fs;
 
 use prost::Message;
-use std::convert::TryFrom;
-use tinymvt::geometry::GeometryDecoder;
+use tinymvt::feature::decode_layer;
+use tinymvt::geometry::DecodedGeometry;
 use tinymvt::tag::TagsDecoder;
-use tinymvt::vector_tile::{tile::GeomType, Tile};
+use tinymvt::vector_tile::{Tile, tile::GeomType};
 
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -31,7 +31,7 @@ fn main() {
 
         let tags_decoder = TagsDecoder::new(&layer.keys, &layer.values);
 
-        for feature in layer.features.iter() {
+        for (feature, decoded) in layer.features.iter().zip(decode_layer(layer)) {
             if let Some(id) = feature.id {
                 println!("feature.id={}", id);
             }
@@ -52,62 +52,45 @@ fn main() {
                 }
             }
 
-            if !feature.geometry.is_empty() {
-                match geom_type {
-                    Some(GeomType::Point) => {
-                        let mut decoder = GeometryDecoder::new(&feature.geometry);
-                        match decoder.decode_points() {
-                            Ok(points) => {
-                                for point in points.iter() {
-                                    println!("feature.point={},{}", point[0], point[1]);
-                                }
-                            }
-                            Err(e) => println!("feature.geometry.error={}", e),
-                        }
+            let decoded = match decoded {
+                Ok(decoded) => decoded,
+                Err(e) => {
+                    println!("feature.error={}", e);
+                    continue;
+                }
+            };
+            match &decoded.geometry {
+                DecodedGeometry::Points(points) => {
+                    for point in points {
+                        println!("feature.point={},{}", point[0], point[1]);
                     }
-                    Some(GeomType::Linestring) => {
-                        let mut decoder = GeometryDecoder::new(&feature.geometry);
-                        match decoder.decode_linestrings() {
-                            Ok(linestrings) => {
-                                for ls in linestrings.iter() {
-                                    for vertex in ls.iter() {
-                                        println!(
-                                            "feature.linestring.vertex={},{}",
-                                            vertex[0], vertex[1]
-                                        );
-                                    }
-                                }
-                            }
-                            Err(e) => println!("feature.geometry.error={}", e),
-                        }
+                }
+                DecodedGeometry::LineStrings(linestrings) => {
+                    for vertex in linestrings.iter().flatten() {
+                        println!("feature.linestring.vertex={},{}", vertex[0], vertex[1]);
                     }
-                    Some(GeomType::Polygon) => {
-                        let mut decoder = GeometryDecoder::new(&feature.geometry);
-                        match decoder.decode_polygons() {
-                            Ok(polygons) => {
-                                for polygon in polygons.iter() {
-                                    for (ring_idx, ring) in polygon.iter().enumerate() {
-                                        let ring_type = if ring_idx == 0 {
-                                            "exterior"
-                                        } else {
-                                            "interior"
-                                        };
-                                        for vertex in ring.iter() {
-                                            println!(
-                                                "feature.polygon.{}.vertex={},{}",
-                                                ring_type, vertex[0], vertex[1]
-                                            );
-                                        }
-                                    }
-                                }
+                }
+                DecodedGeometry::Polygons(polygons) => {
+                    for polygon in polygons {
+                        for (ring_idx, ring) in polygon.iter().enumerate() {
+                            let ring_type = if ring_idx == 0 {
+                                "exterior"
+                            } else {
+                                "interior"
+                            };
+                            for vertex in ring {
+                                println!(
+                                    "feature.polygon.{}.vertex={},{}",
+                                    ring_type, vertex[0], vertex[1]
+                                );
                             }
-                            Err(e) => println!("feature.geometry.error={}", e),
                         }
                     }
-                    _ => {
-                        println!("feature.geometry.raw={}", feature.geometry.len());
-                    }
                 }
+                DecodedGeometry::Unknown(raw) if !raw.is_empty() => {
+                    println!("feature.geometry.raw={}", raw.len());
+                }
+                DecodedGeometry::Unknown(_) => {}
             }
         }
     }
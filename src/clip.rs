@@ -0,0 +1,363 @@
+//! Clipping of tile-local geometries to the tile boundary.
+
+use crate::geometry::normalize_ring;
+
+/// Clips points, line strings and polygon rings to the square `[-buffer, extent + buffer]`.
+///
+/// Line strings are clipped segment by segment (Liang–Barsky), polygon rings are clipped
+/// against each edge of the clip box in turn (Sutherland–Hodgman).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Clipper {
+    min: i32,
+    max: i32,
+}
+
+impl Clipper {
+    /// Creates a clipper for a tile with the given extent and buffer (both in tile units).
+    ///
+    /// Bounds that do not fit in `i32` are saturated.
+    pub fn new(extent: u32, buffer: u32) -> Self {
+        Self {
+            min: -i32::try_from(buffer).unwrap_or(i32::MAX),
+            max: i32::try_from(extent as u64 + buffer as u64).unwrap_or(i32::MAX),
+        }
+    }
+
+    /// Creates a clipper for an arbitrary square `[min, max]`.
+    pub fn from_bounds(min: i32, max: i32) -> Self {
+        debug_assert!(min <= max);
+        Self { min, max }
+    }
+
+    /// Lower bound of the clip box (inclusive).
+    #[inline]
+    pub fn min(&self) -> i32 {
+        self.min
+    }
+
+    /// Upper bound of the clip box (inclusive).
+    #[inline]
+    pub fn max(&self) -> i32 {
+        self.max
+    }
+
+    /// Returns true if the point lies within the clip box (boundary included).
+    #[inline]
+    pub fn contains(&self, [x, y]: [i32; 2]) -> bool {
+        self.min <= x && x <= self.max && self.min <= y && y <= self.max
+    }
+
+    /// Keeps only the points inside the clip box.
+    pub fn clip_points(&self, iterable: impl IntoIterator<Item = [i32; 2]>) -> Vec<[i32; 2]> {
+        iterable.into_iter().filter(|&p| self.contains(p)).collect()
+    }
+
+    /// Clips a line string.
+    ///
+    /// A line that leaves and re-enters the clip box is split into multiple parts.
+    /// Parts with fewer than two distinct vertices are dropped.
+    pub fn clip_linestring(
+        &self,
+        iterable: impl IntoIterator<Item = [i32; 2]>,
+    ) -> Vec<Vec<[i32; 2]>> {
+        let mut parts = Vec::new();
+        let mut current: Vec<[i32; 2]> = Vec::new();
+
+        let mut iter = iterable.into_iter();
+        let Some(mut prev) = iter.next() else {
+            return parts;
+        };
+
+        for p in iter {
+            match self.clip_segment(prev, p) {
+                Some((start, end, entered, exited)) => {
+                    if entered || current.is_empty() {
+                        finish_part(&mut parts, &mut current);
+                        current.push(start);
+                    }
+                    if current.last() != Some(&end) {
+                        current.push(end);
+                    }
+                    if exited {
+                        finish_part(&mut parts, &mut current);
+                    }
+                }
+                None => finish_part(&mut parts, &mut current),
+            }
+            prev = p;
+        }
+        finish_part(&mut parts, &mut current);
+
+        parts
+    }
+
+    /// Clips a polygon ring.
+    ///
+    /// Returns `None` if the ring degenerates (fewer than three distinct vertices or zero area)
+    /// after clipping. The ring may be given either open or closed; the result is always open,
+    /// as expected by [`GeometryEncoder::add_ring`](crate::geometry::GeometryEncoder::add_ring).
+    pub fn clip_ring(&self, iterable: impl IntoIterator<Item = [i32; 2]>) -> Option<Vec<[i32; 2]>> {
+        let mut ring: Vec<[i32; 2]> = iterable.into_iter().collect();
+
+        if !ring.iter().all(|&p| self.contains(p)) {
+            for edge in [Edge::Left, Edge::Right, Edge::Top, Edge::Bottom] {
+                ring = self.clip_ring_by_edge(&ring, edge);
+                if ring.is_empty() {
+                    return None;
+                }
+            }
+        }
+
+        normalize_ring(ring)
+    }
+
+    /// Clips a segment with the Liang–Barsky algorithm.
+    ///
+    /// Returns the clipped endpoints and whether the segment enters / exits the clip box.
+    fn clip_segment(&self, a: [i32; 2], b: [i32; 2]) -> Option<([i32; 2], [i32; 2], bool, bool)> {
+        let (ax, ay) = (a[0] as f64, a[1] as f64);
+        // subtract in f64, the difference of two i32 may not fit in i32
+        let dx = b[0] as f64 - ax;
+        let dy = b[1] as f64 - ay;
+        let (min, max) = (self.min as f64, self.max as f64);
+
+        let mut t0 = 0.0f64;
+        let mut t1 = 1.0f64;
+        for (p, q) in [
+            (-dx, ax - min),
+            (dx, max - ax),
+            (-dy, ay - min),
+            (dy, max - ay),
+        ] {
+            if p == 0.0 {
+                if q < 0.0 {
+                    return None;
+                }
+            } else {
+                let r = q / p;
+                if p < 0.0 {
+                    if r > t1 {
+                        return None;
+                    }
+                    t0 = t0.max(r);
+                } else {
+                    if r < t0 {
+                        return None;
+                    }
+                    t1 = t1.min(r);
+                }
+            }
+        }
+
+        let start = if t0 > 0.0 { self.lerp(a, b, t0) } else { a };
+        let end = if t1 < 1.0 { self.lerp(a, b, t1) } else { b };
+        Some((start, end, t0 > 0.0, t1 < 1.0))
+    }
+
+    /// Interpolates between two points and snaps the result into the clip box.
+    #[inline]
+    fn lerp(&self, a: [i32; 2], b: [i32; 2], t: f64) -> [i32; 2] {
+        let (ax, ay) = (a[0] as f64, a[1] as f64);
+        let x = ax + (b[0] as f64 - ax) * t;
+        let y = ay + (b[1] as f64 - ay) * t;
+        [
+            (x.round() as i32).clamp(self.min, self.max),
+            (y.round() as i32).clamp(self.min, self.max),
+        ]
+    }
+
+    /// One pass of the Sutherland–Hodgman algorithm.
+    fn clip_ring_by_edge(&self, ring: &[[i32; 2]], edge: Edge) -> Vec<[i32; 2]> {
+        let mut output = Vec::with_capacity(ring.len() + 4);
+        let Some(&last) = ring.last() else {
+            return output;
+        };
+
+        let mut prev = last;
+        for &p in ring {
+            let p_inside = self.is_inside(p, edge);
+            let prev_inside = self.is_inside(prev, edge);
+            if p_inside {
+                if !prev_inside {
+                    output.push(self.intersect(prev, p, edge));
+                }
+                output.push(p);
+            } else if prev_inside {
+                output.push(self.intersect(prev, p, edge));
+            }
+            prev = p;
+        }
+        output
+    }
+
+    #[inline]
+    fn is_inside(&self, [x, y]: [i32; 2], edge: Edge) -> bool {
+        match edge {
+            Edge::Left => x >= self.min,
+            Edge::Right => x <= self.max,
+            Edge::Top => y >= self.min,
+            Edge::Bottom => y <= self.max,
+        }
+    }
+
+    /// Intersection of the segment `a`-`b` with the line through the given edge.
+    fn intersect(&self, a: [i32; 2], b: [i32; 2], edge: Edge) -> [i32; 2] {
+        let (ax, ay) = (a[0] as f64, a[1] as f64);
+        // subtract in f64, the difference of two i32 may not fit in i32
+        let dx = b[0] as f64 - ax;
+        let dy = b[1] as f64 - ay;
+        match edge {
+            Edge::Left | Edge::Right => {
+                let x = if edge == Edge::Left {
+                    self.min
+                } else {
+                    self.max
+                };
+                let y = ay + (x as f64 - ax) * dy / dx;
+                [x, y.round() as i32]
+            }
+            Edge::Top | Edge::Bottom => {
+                let y = if edge == Edge::Top {
+                    self.min
+                } else {
+                    self.max
+                };
+                let x = ax + (y as f64 - ay) * dx / dy;
+                [x.round() as i32, y]
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+#[inline]
+fn finish_part(parts: &mut Vec<Vec<[i32; 2]>>, current: &mut Vec<[i32; 2]>) {
+    if current.len() >= 2 {
+        parts.push(std::mem::take(current));
+    } else {
+        current.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::calculate_signed_area;
+
+    #[test]
+    fn test_clip_points() {
+        let clipper = Clipper::new(4096, 64);
+        let points =
+            clipper.clip_points([[0, 0], [-64, 4160], [-65, 0], [100, 5000], [2048, 2048]]);
+        assert_eq!(points, [[0, 0], [-64, 4160], [2048, 2048]]);
+    }
+
+    #[test]
+    fn test_clip_linestring_inside() {
+        let clipper = Clipper::new(4096, 0);
+        let parts = clipper.clip_linestring([[0, 0], [100, 100], [200, 0]]);
+        assert_eq!(parts, [vec![[0, 0], [100, 100], [200, 0]]]);
+    }
+
+    #[test]
+    fn test_clip_linestring_crossing() {
+        let clipper = Clipper::new(100, 0);
+        let parts = clipper.clip_linestring([[-50, 50], [150, 50]]);
+        assert_eq!(parts, [vec![[0, 50], [100, 50]]]);
+
+        let parts = clipper.clip_linestring([[50, 50], [150, 150]]);
+        assert_eq!(parts, [vec![[50, 50], [100, 100]]]);
+    }
+
+    #[test]
+    fn test_clip_linestring_reentering() {
+        // leaves the tile through the right edge and comes back
+        let clipper = Clipper::new(100, 10);
+        let parts = clipper.clip_linestring([[50, 20], [200, 20], [200, 80], [50, 80]]);
+        assert_eq!(
+            parts,
+            [vec![[50, 20], [110, 20]], vec![[110, 80], [50, 80]]]
+        );
+    }
+
+    #[test]
+    fn test_clip_linestring_outside() {
+        let clipper = Clipper::new(100, 0);
+        assert!(clipper.clip_linestring([[200, 0], [300, 100]]).is_empty());
+        assert!(clipper.clip_linestring([[50, 50]]).is_empty());
+        assert!(clipper.clip_linestring([]).is_empty());
+    }
+
+    #[test]
+    fn test_clip_ring_inside() {
+        let clipper = Clipper::new(4096, 0);
+        let ring = [[0, 0], [100, 0], [100, 100], [0, 100]];
+        assert_eq!(clipper.clip_ring(ring).unwrap(), ring);
+        // closed ring is opened
+        let closed = [[0, 0], [100, 0], [100, 100], [0, 100], [0, 0]];
+        assert_eq!(clipper.clip_ring(closed).unwrap(), ring);
+    }
+
+    #[test]
+    fn test_clip_ring_partial() {
+        let clipper = Clipper::new(100, 0);
+        let clipped = clipper
+            .clip_ring([[50, 50], [150, 50], [150, 150], [50, 150]])
+            .unwrap();
+        assert_eq!(clipped, [[50, 100], [50, 50], [100, 50], [100, 100]]);
+        assert!(calculate_signed_area(&clipped) > 0.0);
+    }
+
+    #[test]
+    fn test_clip_ring_covering() {
+        let clipper = Clipper::new(100, 0);
+        let clipped = clipper
+            .clip_ring([[-10, -10], [110, -10], [110, 110], [-10, 110]])
+            .unwrap();
+        assert_eq!(calculate_signed_area(&clipped), 100.0 * 100.0);
+    }
+
+    #[test]
+    fn test_clip_ring_degenerate() {
+        let clipper = Clipper::new(100, 0);
+        // completely outside
+        assert!(
+            clipper
+                .clip_ring([[200, 0], [300, 0], [300, 100]])
+                .is_none()
+        );
+        // touches the boundary only along an edge
+        assert!(
+            clipper
+                .clip_ring([[100, 0], [200, 0], [200, 100], [100, 100]])
+                .is_none()
+        );
+        // too few vertices
+        assert!(clipper.clip_ring([[0, 0], [10, 10]]).is_none());
+    }
+
+    #[test]
+    fn test_clip_extreme_coordinates() {
+        let (min, max) = (i32::MIN, i32::MAX);
+        let clipper = Clipper::new(4096, 64);
+        let parts = clipper.clip_linestring([[min, min], [max, max]]);
+        assert_eq!(parts, [vec![[-64, -64], [4160, 4160]]]);
+        let parts = clipper.clip_linestring([[-5, 0], [max, 0]]);
+        assert_eq!(parts, [vec![[-5, 0], [4160, 0]]]);
+
+        let clipped = clipper
+            .clip_ring([[min, min], [max, min], [max, max], [min, max]])
+            .unwrap();
+        assert_eq!(calculate_signed_area(&clipped), 4224.0 * 4224.0);
+
+        let clipper = Clipper::new(u32::MAX, u32::MAX);
+        assert_eq!((clipper.min(), clipper.max()), (-max, max));
+    }
+}
@@ -0,0 +1,320 @@
+//! Computing the set of tiles touched by geometries in geographic coordinates.
+
+use foldhash::{HashMap, HashSet};
+
+use crate::TileZXY;
+use crate::webmercator::lnglat_to_web_mercator;
+
+/// Accumulates the tiles at a zoom level touched by points, lines and polygons in (lng, lat).
+///
+/// Lines and polygon boundaries are traced cell by cell through tile space, and polygon
+/// interiors are filled by scanline rasterization, so the cover is exact rather than a
+/// bounding box. Holes that contain whole tiles leave those tiles out.
+pub struct TileCover {
+    zoom: u8,
+    tiles: HashSet<(u32, u32)>,
+}
+
+impl TileCover {
+    /// Creates an empty cover at the zoom level.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `zoom` is greater than 31.
+    pub fn new(zoom: u8) -> Self {
+        assert!(zoom <= 31, "zoom level must be at most 31");
+        Self {
+            zoom,
+            tiles: HashSet::default(),
+        }
+    }
+
+    /// Adds the tiles containing the points.
+    pub fn add_points(&mut self, iterable: impl IntoIterator<Item = [f64; 2]>) {
+        for [lng, lat] in iterable {
+            let [x, y] = self.project(lng, lat);
+            self.insert(x.floor() as i64, y.floor() as i64);
+        }
+    }
+
+    /// Adds the tiles touched by a line string.
+    pub fn add_linestring(&mut self, iterable: impl IntoIterator<Item = [f64; 2]>) {
+        let points: Vec<[f64; 2]> = iterable
+            .into_iter()
+            .map(|[lng, lat]| self.project(lng, lat))
+            .collect();
+        match points.as_slice() {
+            [] => {}
+            [p] => self.insert(p[0].floor() as i64, p[1].floor() as i64),
+            _ => {
+                for w in points.windows(2) {
+                    self.add_segment(w[0], w[1]);
+                }
+            }
+        }
+    }
+
+    /// Adds the tiles touched by a polygon given as an exterior ring followed by holes.
+    ///
+    /// Rings may be given open or closed, in any winding order.
+    pub fn add_polygon<R>(&mut self, rings: impl IntoIterator<Item = R>)
+    where
+        R: IntoIterator<Item = [f64; 2]>,
+    {
+        let rings: Vec<Vec<[f64; 2]>> = rings
+            .into_iter()
+            .map(|ring| {
+                ring.into_iter()
+                    .map(|[lng, lat]| self.project(lng, lat))
+                    .collect()
+            })
+            .filter(|ring: &Vec<[f64; 2]>| !ring.is_empty())
+            .collect();
+        if rings.is_empty() {
+            return;
+        }
+
+        // boundary
+        for ring in &rings {
+            for i in 0..ring.len() {
+                self.add_segment(ring[i], ring[(i + 1) % ring.len()]);
+            }
+        }
+
+        // interior: a tile not touched by the boundary is inside iff its center is inside
+        let (min_y, max_y) = rings
+            .iter()
+            .flatten()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| {
+                (lo.min(p[1]), hi.max(p[1]))
+            });
+        let mut crossings = Vec::new();
+        for row in (min_y.floor() as i64)..=(max_y.floor() as i64) {
+            let cy = row as f64 + 0.5;
+            crossings.clear();
+            for ring in &rings {
+                for i in 0..ring.len() {
+                    let (p, q) = (ring[i], ring[(i + 1) % ring.len()]);
+                    if (p[1] <= cy) != (q[1] <= cy) {
+                        crossings.push(p[0] + (cy - p[1]) * (q[0] - p[0]) / (q[1] - p[1]));
+                    }
+                }
+            }
+            crossings.sort_by(f64::total_cmp);
+            for pair in crossings.chunks_exact(2) {
+                let first = (pair[0] - 0.5).ceil() as i64;
+                let last = (pair[1] - 0.5).floor() as i64;
+                for col in first..=last {
+                    self.insert(col, row);
+                }
+            }
+        }
+    }
+
+    /// Returns the zoom level of the cover.
+    #[inline]
+    pub fn zoom(&self) -> u8 {
+        self.zoom
+    }
+
+    /// Returns the number of tiles in the cover.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Returns true if no tiles have been covered.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// Returns the covered tiles, sorted by (x, y).
+    pub fn tiles(&self) -> Vec<TileZXY> {
+        let mut tiles: Vec<TileZXY> = self.tiles.iter().map(|&(x, y)| (self.zoom, x, y)).collect();
+        tiles.sort_unstable();
+        tiles
+    }
+
+    /// Returns the covered tiles, replacing every complete set of four siblings with
+    /// their parent, recursively up to `min_zoom`.
+    ///
+    /// The result contains tiles between `min_zoom` and the cover's zoom, sorted by (z, x, y).
+    pub fn coalesced(&self, min_zoom: u8) -> Vec<TileZXY> {
+        let mut result = Vec::new();
+        let mut current: HashSet<(u32, u32)> = self.tiles.clone();
+        for z in (min_zoom.min(self.zoom) + 1..=self.zoom).rev() {
+            let mut children: HashMap<(u32, u32), u8> = HashMap::default();
+            for &(x, y) in &current {
+                *children.entry((x >> 1, y >> 1)).or_default() += 1;
+            }
+            let mut parents = HashSet::default();
+            for (x, y) in current {
+                if children[&(x >> 1, y >> 1)] == 4 {
+                    parents.insert((x >> 1, y >> 1));
+                } else {
+                    result.push((z, x, y));
+                }
+            }
+            current = parents;
+        }
+        let min_zoom = min_zoom.min(self.zoom);
+        result.extend(current.into_iter().map(|(x, y)| (min_zoom, x, y)));
+        result.sort_unstable();
+        result
+    }
+
+    /// Projects (lng, lat) to continuous tile space at the cover's zoom.
+    #[inline]
+    fn project(&self, lng: f64, lat: f64) -> [f64; 2] {
+        let n = (1u64 << self.zoom) as f64;
+        let (mx, my) = lnglat_to_web_mercator(lng.clamp(-180.0, 180.0), lat);
+        [mx * n, (my * n).clamp(0.0, n)]
+    }
+
+    #[inline]
+    fn insert(&mut self, x: i64, y: i64) {
+        let max = (1i64 << self.zoom) - 1;
+        self.tiles
+            .insert((x.clamp(0, max) as u32, y.clamp(0, max) as u32));
+    }
+
+    /// Adds the cells crossed by a segment in tile space (Amanatides–Woo grid traversal).
+    fn add_segment(&mut self, a: [f64; 2], b: [f64; 2]) {
+        let (mut x, mut y) = (a[0].floor() as i64, a[1].floor() as i64);
+        let (x1, y1) = (b[0].floor() as i64, b[1].floor() as i64);
+        self.insert(x, y);
+
+        let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+        let (step_x, step_y) = (dx.signum() as i64, dy.signum() as i64);
+        let boundary = |cell: i64, d: f64| {
+            if d > 0.0 {
+                (cell + 1) as f64
+            } else {
+                cell as f64
+            }
+        };
+        let mut t_max_x = if dx != 0.0 {
+            (boundary(x, dx) - a[0]) / dx
+        } else {
+            f64::INFINITY
+        };
+        let mut t_max_y = if dy != 0.0 {
+            (boundary(y, dy) - a[1]) / dy
+        } else {
+            f64::INFINITY
+        };
+        let (t_delta_x, t_delta_y) = (1.0 / dx.abs(), 1.0 / dy.abs());
+
+        for _ in 0..(x1 - x).abs() + (y1 - y).abs() {
+            if t_max_x < t_max_y && x != x1 || y == y1 {
+                x += step_x;
+                t_max_x += t_delta_x;
+            } else {
+                y += step_y;
+                t_max_y += t_delta_y;
+            }
+            self.insert(x, y);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webmercator::{lnglat_to_zxy, zxy_to_lnglat_bounds};
+
+    #[test]
+    fn test_points() {
+        let mut cover = TileCover::new(13);
+        cover.add_points([
+            [138.28421421786732, 37.153461188900344],
+            [138.322514014752, 37.108119251859506],
+        ]);
+        assert_eq!(cover.tiles(), [(13, 7242, 3184), (13, 7243, 3185)]);
+
+        // out-of-range coordinates are clamped to the edge tiles
+        let mut cover = TileCover::new(2);
+        cover.add_points([[180.0, 89.0], [-200.0, -89.0]]);
+        assert_eq!(cover.tiles(), [(2, 0, 3), (2, 3, 0)]);
+    }
+
+    #[test]
+    fn test_linestring() {
+        // a horizontal line across the world
+        let mut cover = TileCover::new(2);
+        cover.add_linestring([[-170.0, 10.0], [170.0, 10.0]]);
+        assert_eq!(cover.tiles(), [(2, 0, 1), (2, 1, 1), (2, 2, 1), (2, 3, 1)]);
+
+        // a diagonal line only touches the tiles it crosses, not its bbox
+        let mut cover = TileCover::new(4);
+        cover.add_linestring([[-170.0, 80.0], [170.0, -80.0]]);
+        let tiles = cover.tiles();
+        assert!(tiles.len() < 16 * 16 / 2);
+        assert!(tiles.contains(&lnglat_to_zxy(4, -170.0, 80.0)));
+        assert!(tiles.contains(&lnglat_to_zxy(4, 170.0, -80.0)));
+        assert!(tiles.contains(&lnglat_to_zxy(4, 0.0, 0.0)));
+        assert!(!tiles.contains(&lnglat_to_zxy(4, 170.0, 80.0)));
+        // consecutive tiles are 4-connected
+        for (z, x, y) in &tiles {
+            let neighbors = [
+                (x + 1, *y),
+                (x.wrapping_sub(1), *y),
+                (*x, y + 1),
+                (*x, y.wrapping_sub(1)),
+            ];
+            assert!(
+                neighbors
+                    .iter()
+                    .any(|&(nx, ny)| tiles.contains(&(*z, nx, ny)))
+            );
+        }
+    }
+
+    #[test]
+    fn test_polygon_with_hole() {
+        let square = |[w, s, e, n]: [f64; 4]| vec![[w, s], [e, s], [e, n], [w, n]];
+        let mut cover = TileCover::new(4);
+        // the exterior lies within tiles 2..=13, and the hole slightly exceeds tiles 5..=10
+        let [w0, s0, ..] = zxy_to_lnglat_bounds((4, 2, 13));
+        let [.., e0, n0] = zxy_to_lnglat_bounds((4, 13, 2));
+        let [w1, s1, ..] = zxy_to_lnglat_bounds((4, 5, 10));
+        let [.., e1, n1] = zxy_to_lnglat_bounds((4, 10, 5));
+        let inset = 0.5;
+        cover.add_polygon([
+            square([w0 + inset, s0 + inset, e0 - inset, n0 - inset]),
+            square([w1 - inset, s1 - inset, e1 + inset, n1 + inset]),
+        ]);
+        let tiles = cover.tiles();
+        // 12x12 outer minus the 6x6 fully covered by the hole (5..=10)
+        assert_eq!(tiles.len(), 12 * 12 - 6 * 6);
+        assert!(tiles.contains(&(4, 2, 2)));
+        assert!(tiles.contains(&(4, 4, 4)));
+        assert!(tiles.contains(&(4, 11, 8)));
+        assert!(!tiles.contains(&(4, 5, 5)));
+        assert!(!tiles.contains(&(4, 7, 7)));
+        assert!(!tiles.contains(&(4, 1, 1)));
+    }
+
+    #[test]
+    fn test_coalesced() {
+        // a polygon covering exactly the north-west quarter of the world
+        let mut cover = TileCover::new(3);
+        let inset = 1e-6;
+        cover.add_polygon([[
+            [-180.0 + inset, inset],
+            [-inset, inset],
+            [-inset, 85.0],
+            [-180.0 + inset, 85.0],
+        ]]);
+        assert_eq!(cover.len(), 16);
+        assert_eq!(cover.coalesced(0), [(1, 0, 0)]);
+        assert_eq!(cover.coalesced(2).len(), 4);
+        assert_eq!(cover.coalesced(3).len(), 16);
+
+        // an incomplete set of siblings is not coalesced
+        let mut cover = TileCover::new(2);
+        cover.add_points([[-170.0, 80.0], [-80.0, 80.0], [-170.0, 10.0]]);
+        assert_eq!(cover.coalesced(0), [(2, 0, 0), (2, 0, 1), (2, 1, 0)]);
+    }
+}
@@ -0,0 +1,248 @@
+//! Error type for decoding MVT.
+
+use std::fmt;
+
+/// Errors that can occur while decoding tiles, geometries and tags, importing GeoJSON,
+/// or reading tile archives.
+///
+/// Offsets in geometry errors are indices into the geometry `u32` buffer;
+/// offsets in protobuf errors are byte offsets within the message being parsed.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// A geometry command other than the expected one was found.
+    UnexpectedCommand {
+        expected: u32,
+        found: u32,
+        offset: usize,
+    },
+    /// A geometry command has an ID other than MoveTo, LineTo or ClosePath.
+    UnknownCommand { command: u32, offset: usize },
+    /// A geometry command has a count that is not allowed in its position.
+    InvalidCommandCount {
+        command: u32,
+        count: u32,
+        offset: usize,
+    },
+    /// The buffer ended in the middle of a command, parameter or message.
+    Truncated { offset: usize },
+    /// A geometry parameter moves the cursor outside the `i32` range.
+    CoordinateOverflow { offset: usize },
+    /// The tags array of a feature has an odd length.
+    OddTagsLength { len: usize },
+    /// A tag refers to a key that is not in the layer's keys dictionary.
+    KeyIndexOutOfBounds { index: usize },
+    /// A tag refers to a value that is not in the layer's values dictionary.
+    ValueIndexOutOfBounds { index: usize },
+    /// A value in the layer's values dictionary has no field set.
+    InvalidValue { index: usize },
+    /// A protobuf varint is longer than 10 bytes.
+    InvalidVarint { offset: usize },
+    /// A message could not be decoded by prost.
+    Protobuf(prost::DecodeError),
+    /// A protobuf field has an unsupported wire type.
+    InvalidWireType { wire_type: u8, offset: usize },
+    /// A protobuf string is not valid UTF-8.
+    InvalidUtf8 { offset: usize },
+    /// The input GeoJSON is malformed or uses an unsupported construct.
+    InvalidGeoJson { reason: String },
+    /// An I/O operation on an archive failed.
+    Io(std::io::Error),
+    /// A PMTiles archive has an invalid header or directory.
+    InvalidPmTiles { reason: String },
+    /// Data is compressed with a codec that is not supported.
+    UnsupportedCompression { compression: u8 },
+    /// A database operation on an MBTiles file failed.
+    #[cfg(feature = "mbtiles")]
+    Database(rusqlite::Error),
+    /// An MBTiles file has invalid contents.
+    InvalidMbTiles { reason: String },
+    /// A tile is not a descendant of the given ancestor tile.
+    NotDescendant {
+        ancestor: crate::TileZXY,
+        tile: crate::TileZXY,
+    },
+}
+
+/// Result type with [`Error`].
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Error::*;
+        match self {
+            UnexpectedCommand {
+                expected,
+                found,
+                offset,
+            } => write!(
+                f,
+                "expected {} command, got {} at offset {}",
+                command_name(*expected),
+                command_name(*found),
+                offset
+            ),
+            UnknownCommand { command, offset } => {
+                write!(f, "unknown command {} at offset {}", command, offset)
+            }
+            InvalidCommandCount {
+                command,
+                count,
+                offset,
+            } => write!(
+                f,
+                "invalid count {} for {} command at offset {}",
+                count,
+                command_name(*command),
+                offset
+            ),
+            Truncated { offset } => write!(f, "unexpected end of buffer at offset {}", offset),
+            CoordinateOverflow { offset } => write!(f, "coordinate overflow at offset {}", offset),
+            OddTagsLength { len } => write!(f, "tags array must have even length, got {}", len),
+            KeyIndexOutOfBounds { index } => write!(f, "key index {} out of bounds", index),
+            ValueIndexOutOfBounds { index } => write!(f, "value index {} out of bounds", index),
+            InvalidValue { index } => write!(f, "invalid tile value at index {}", index),
+            InvalidVarint { offset } => write!(f, "invalid varint at offset {}", offset),
+            Protobuf(err) => write!(f, "{}", err),
+            InvalidWireType { wire_type, offset } => {
+                write!(f, "invalid wire type {} at offset {}", wire_type, offset)
+            }
+            InvalidUtf8 { offset } => write!(f, "invalid UTF-8 string at offset {}", offset),
+            InvalidGeoJson { reason } => write!(f, "invalid GeoJSON: {}", reason),
+            Io(err) => write!(f, "I/O error: {}", err),
+            InvalidPmTiles { reason } => write!(f, "invalid PMTiles archive: {}", reason),
+            UnsupportedCompression { compression } => {
+                write!(f, "unsupported compression type {}", compression)
+            }
+            #[cfg(feature = "mbtiles")]
+            Database(err) => write!(f, "database error: {}", err),
+            InvalidMbTiles { reason } => write!(f, "invalid MBTiles file: {}", reason),
+            NotDescendant { ancestor, tile } => {
+                write!(f, "tile {:?} is not a descendant of {:?}", tile, ancestor)
+            }
+        }
+    }
+}
+
+/// Errors wrapping an I/O error are compared by [`ErrorKind`](std::io::ErrorKind) only.
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        use Error::*;
+        match (self, other) {
+            (
+                UnexpectedCommand {
+                    expected: e1,
+                    found: f1,
+                    offset: o1,
+                },
+                UnexpectedCommand {
+                    expected: e2,
+                    found: f2,
+                    offset: o2,
+                },
+            ) => (e1, f1, o1) == (e2, f2, o2),
+            (
+                UnknownCommand {
+                    command: c1,
+                    offset: o1,
+                },
+                UnknownCommand {
+                    command: c2,
+                    offset: o2,
+                },
+            ) => (c1, o1) == (c2, o2),
+            (
+                InvalidCommandCount {
+                    command: c1,
+                    count: n1,
+                    offset: o1,
+                },
+                InvalidCommandCount {
+                    command: c2,
+                    count: n2,
+                    offset: o2,
+                },
+            ) => (c1, n1, o1) == (c2, n2, o2),
+            (Truncated { offset: o1 }, Truncated { offset: o2 })
+            | (CoordinateOverflow { offset: o1 }, CoordinateOverflow { offset: o2 })
+            | (InvalidVarint { offset: o1 }, InvalidVarint { offset: o2 })
+            | (InvalidUtf8 { offset: o1 }, InvalidUtf8 { offset: o2 }) => o1 == o2,
+            (OddTagsLength { len: i1 }, OddTagsLength { len: i2 })
+            | (KeyIndexOutOfBounds { index: i1 }, KeyIndexOutOfBounds { index: i2 })
+            | (ValueIndexOutOfBounds { index: i1 }, ValueIndexOutOfBounds { index: i2 })
+            | (InvalidValue { index: i1 }, InvalidValue { index: i2 }) => i1 == i2,
+            (Protobuf(e1), Protobuf(e2)) => e1 == e2,
+            (
+                InvalidWireType {
+                    wire_type: w1,
+                    offset: o1,
+                },
+                InvalidWireType {
+                    wire_type: w2,
+                    offset: o2,
+                },
+            ) => (w1, o1) == (w2, o2),
+            (InvalidGeoJson { reason: r1 }, InvalidGeoJson { reason: r2 })
+            | (InvalidPmTiles { reason: r1 }, InvalidPmTiles { reason: r2 })
+            | (InvalidMbTiles { reason: r1 }, InvalidMbTiles { reason: r2 }) => r1 == r2,
+            (Io(e1), Io(e2)) => e1.kind() == e2.kind(),
+            (
+                UnsupportedCompression { compression: c1 },
+                UnsupportedCompression { compression: c2 },
+            ) => c1 == c2,
+            #[cfg(feature = "mbtiles")]
+            (Database(e1), Database(e2)) => e1 == e2,
+            (
+                NotDescendant {
+                    ancestor: a1,
+                    tile: t1,
+                },
+                NotDescendant {
+                    ancestor: a2,
+                    tile: t2,
+                },
+            ) => (a1, t1) == (a2, t2),
+            _ => false,
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            Error::Protobuf(err) => Some(err),
+            #[cfg(feature = "mbtiles")]
+            Error::Database(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<prost::DecodeError> for Error {
+    fn from(err: prost::DecodeError) -> Self {
+        Error::Protobuf(err)
+    }
+}
+
+#[cfg(feature = "mbtiles")]
+impl From<rusqlite::Error> for Error {
+    fn from(err: rusqlite::Error) -> Self {
+        Error::Database(err)
+    }
+}
+
+fn command_name(command: u32) -> String {
+    match command {
+        1 => "MoveTo".to_string(),
+        2 => "LineTo".to_string(),
+        7 => "ClosePath".to_string(),
+        other => format!("unknown ({})", other),
+    }
+}
@@ -0,0 +1,169 @@
+//! Decoding features into typed geometries and properties.
+
+use crate::error::Result;
+use crate::geometry::{DecodedGeometry, GeometryDecoder, RingClassification};
+use crate::tag::{TagsDecoder, Value};
+use crate::vector_tile::tile::{Feature, GeomType, Layer};
+
+/// A feature with its geometry and tags decoded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedFeature<'a> {
+    pub id: Option<u64>,
+    pub geometry: DecodedGeometry,
+    /// Key-value pairs in the order of the feature's tags.
+    pub properties: Vec<(&'a str, Value)>,
+}
+
+/// Decodes a feature according to its `Feature.type`.
+///
+/// Features of unknown or missing geometry type keep their undecoded commands in
+/// [`DecodedGeometry::Unknown`]. Polygon rings are grouped by winding order; use
+/// [`decode_layer`] to pick the ring classification from the layer version.
+pub fn decode_feature<'a>(feature: &Feature, tags: &TagsDecoder<'a>) -> Result<DecodedFeature<'a>> {
+    decode_feature_with(feature, tags, RingClassification::Winding)
+}
+
+/// Returns an iterator that decodes every feature of a layer.
+pub fn decode_layer(layer: &Layer) -> DecodedFeatures<'_> {
+    DecodedFeatures {
+        features: layer.features.iter(),
+        tags: TagsDecoder::new(&layer.keys, &layer.values),
+        ring_classification: RingClassification::for_version(layer.version),
+    }
+}
+
+/// Iterator over the decoded features of a layer, created by [`decode_layer`].
+pub struct DecodedFeatures<'a> {
+    features: std::slice::Iter<'a, Feature>,
+    tags: TagsDecoder<'a>,
+    ring_classification: RingClassification,
+}
+
+impl<'a> Iterator for DecodedFeatures<'a> {
+    type Item = Result<DecodedFeature<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let feature = self.features.next()?;
+        Some(decode_feature_with(
+            feature,
+            &self.tags,
+            self.ring_classification,
+        ))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.features.size_hint()
+    }
+}
+
+impl ExactSizeIterator for DecodedFeatures<'_> {}
+
+fn decode_feature_with<'a>(
+    feature: &Feature,
+    tags: &TagsDecoder<'a>,
+    ring_classification: RingClassification,
+) -> Result<DecodedFeature<'a>> {
+    let mut decoder = GeometryDecoder::new(&feature.geometry);
+    decoder.set_ring_classification(ring_classification);
+    let geom_type = feature.r#type.and_then(|t| GeomType::try_from(t).ok());
+    let geometry = match geom_type {
+        Some(GeomType::Point) => DecodedGeometry::Points(decoder.decode_points()?),
+        Some(GeomType::Linestring) => DecodedGeometry::LineStrings(decoder.decode_linestrings()?),
+        Some(GeomType::Polygon) => DecodedGeometry::Polygons(decoder.decode_polygons()?),
+        Some(GeomType::Unknown) | None => DecodedGeometry::Unknown(feature.geometry.clone()),
+    };
+    Ok(DecodedFeature {
+        id: feature.id,
+        geometry,
+        properties: tags.decode(&feature.tags)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::GeometryEncoder;
+    use crate::tag::TagsEncoder;
+
+    #[test]
+    fn test_decode_layer() {
+        let mut tags_enc = TagsEncoder::new();
+        let mut features = Vec::new();
+
+        let mut geom_enc = GeometryEncoder::new();
+        geom_enc.add_linestring([[0, 0], [10, 10]]);
+        tags_enc.add("class", "road");
+        features.push(Feature {
+            id: Some(1),
+            tags: tags_enc.take_tags(),
+            r#type: Some(GeomType::Linestring as i32),
+            geometry: geom_enc.into_vec(),
+        });
+
+        // counter-clockwise shell with a clockwise hole, only valid in v1
+        let mut geom_enc = GeometryEncoder::new();
+        geom_enc.add_ring([[0, 0], [0, 10], [10, 10], [10, 0]]);
+        geom_enc.add_ring([[2, 2], [4, 2], [4, 4], [2, 4]]);
+        features.push(Feature {
+            id: None,
+            tags: vec![],
+            r#type: Some(GeomType::Polygon as i32),
+            geometry: geom_enc.into_vec(),
+        });
+
+        features.push(Feature {
+            id: Some(3),
+            tags: vec![],
+            r#type: Some(42),
+            geometry: vec![9, 2, 2],
+        });
+
+        let (keys, values) = tags_enc.into_keys_and_values();
+        let mut layer = Layer {
+            version: 1,
+            name: "test".to_string(),
+            features,
+            keys,
+            values,
+            extent: Some(4096),
+        };
+
+        let decoded: Vec<_> = decode_layer(&layer).collect::<Result<_>>().unwrap();
+        assert_eq!(decoded.len(), 3);
+        assert_eq!(
+            decoded[0],
+            DecodedFeature {
+                id: Some(1),
+                geometry: DecodedGeometry::LineStrings(vec![vec![[0, 0], [10, 10]]]),
+                properties: vec![("class", Value::from("road"))],
+            }
+        );
+        let DecodedGeometry::Polygons(polygons) = &decoded[1].geometry else {
+            panic!("expected polygons");
+        };
+        assert_eq!(polygons.len(), 1);
+        assert_eq!(polygons[0].len(), 2);
+        assert_eq!(decoded[2].geometry, DecodedGeometry::Unknown(vec![9, 2, 2]));
+
+        // v2 groups the same rings by winding order
+        layer.version = 2;
+        let tags = TagsDecoder::new(&layer.keys, &layer.values);
+        let feature = decode_feature(&layer.features[1], &tags).unwrap();
+        let DecodedGeometry::Polygons(polygons) = feature.geometry else {
+            panic!("expected polygons");
+        };
+        assert_eq!(polygons.len(), 2);
+    }
+
+    #[test]
+    fn test_decode_feature_errors() {
+        let feature = Feature {
+            id: None,
+            tags: vec![0],
+            r#type: Some(GeomType::Point as i32),
+            geometry: vec![9, 0, 0],
+        };
+        let tags = TagsDecoder::new(&[], &[]);
+        assert!(decode_feature(&feature, &tags).is_err());
+    }
+}
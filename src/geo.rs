@@ -0,0 +1,332 @@
+//! Conversion between decoded geometries and [`geo_types`] geometries.
+
+use geo_types::{
+    Coord, CoordNum, Geometry, LineString, MultiLineString, MultiPoint, MultiPolygon, Point,
+    Polygon,
+};
+
+use crate::TileZXY;
+use crate::geometry::{DecodedGeometry, GeometryEncoder};
+use crate::vector_tile::tile::GeomType;
+use crate::webmercator::{lnglat_to_tile_coord, tile_coord_to_lnglat};
+
+/// Converts a decoded geometry to a `geo_types` geometry in tile coordinates.
+///
+/// Single-part geometries become `Point`, `LineString` or `Polygon`; multi-part geometries become
+/// their `Multi*` counterparts. Returns `None` for [`DecodedGeometry::Unknown`].
+pub fn to_geo(geometry: &DecodedGeometry) -> Option<Geometry<i32>> {
+    convert(geometry, |[x, y]| Coord { x, y })
+}
+
+/// Converts a decoded geometry to a `geo_types` geometry in tile coordinates as `f64`.
+pub fn to_geo_f64(geometry: &DecodedGeometry) -> Option<Geometry<f64>> {
+    convert(geometry, |[x, y]| Coord {
+        x: x as f64,
+        y: y as f64,
+    })
+}
+
+/// Converts a decoded geometry of the given tile to a `geo_types` geometry in (lng, lat).
+pub fn to_geo_lnglat(
+    geometry: &DecodedGeometry,
+    tile: TileZXY,
+    extent: u32,
+) -> Option<Geometry<f64>> {
+    convert(geometry, |coord| {
+        let (x, y) = tile_coord_to_lnglat(tile, extent, coord);
+        Coord { x, y }
+    })
+}
+
+/// Projects a `geo_types` geometry in (lng, lat) to tile coordinates of the given tile.
+///
+/// Coordinates far outside the tile are clamped as in [`lnglat_to_tile_coord`], so the result
+/// can be passed to an encoder created with [`GeometryEncoder::with_clipper`].
+pub fn lnglat_to_tile_geometry(
+    geometry: &Geometry<f64>,
+    tile: TileZXY,
+    extent: u32,
+) -> Geometry<i32> {
+    map_geometry(geometry, &|coord: Coord<f64>| {
+        let [x, y] = lnglat_to_tile_coord(tile, extent, coord.x, coord.y);
+        Coord { x, y }
+    })
+}
+
+impl GeometryEncoder {
+    /// Adds a `geo_types` geometry in tile coordinates and returns its type for `Feature.type`.
+    ///
+    /// Polygons are added with [`add_polygon`](Self::add_polygon), so the winding order of
+    /// each ring is corrected as needed. `Line`, `Rect` and `Triangle` are added as line
+    /// strings and polygons. A `GeometryCollection` is added if all its members have the same
+    /// type; otherwise, or if it is empty, nothing is added and `None` is returned.
+    pub fn add_geo(&mut self, geometry: &Geometry<i32>) -> Option<GeomType> {
+        let geom_type = geo_geom_type(geometry)?;
+        // points of all members go into a single MoveTo command
+        let mut points = Vec::new();
+        self.add_geo_inner(geometry, &mut points);
+        self.add_points(points);
+        Some(geom_type)
+    }
+
+    fn add_geo_inner(&mut self, geometry: &Geometry<i32>, points: &mut Vec<[i32; 2]>) {
+        let coords = |line: &LineString<i32>| line.0.iter().map(|c| [c.x, c.y]).collect::<Vec<_>>();
+        let polygon = |encoder: &mut Self, polygon: &Polygon<i32>| {
+            encoder.add_polygon(
+                std::iter::once(polygon.exterior())
+                    .chain(polygon.interiors())
+                    .map(coords),
+            )
+        };
+        match geometry {
+            Geometry::Point(p) => points.push([p.x(), p.y()]),
+            Geometry::MultiPoint(multi) => points.extend(multi.iter().map(|p| [p.x(), p.y()])),
+            Geometry::Line(line) => {
+                self.add_linestring([[line.start.x, line.start.y], [line.end.x, line.end.y]])
+            }
+            Geometry::LineString(line) => self.add_linestring(coords(line)),
+            Geometry::MultiLineString(lines) => {
+                for line in lines {
+                    self.add_linestring(coords(line));
+                }
+            }
+            Geometry::Polygon(p) => polygon(self, p),
+            Geometry::MultiPolygon(polygons) => {
+                for p in polygons {
+                    polygon(self, p);
+                }
+            }
+            Geometry::Rect(rect) => polygon(self, &rect.to_polygon()),
+            Geometry::Triangle(triangle) => polygon(self, &triangle.to_polygon()),
+            Geometry::GeometryCollection(collection) => {
+                for geometry in collection {
+                    self.add_geo_inner(geometry, points);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the MVT geometry type of a `geo_types` geometry, or `None` for empty or mixed collections.
+fn geo_geom_type<T: CoordNum>(geometry: &Geometry<T>) -> Option<GeomType> {
+    match geometry {
+        Geometry::Point(_) | Geometry::MultiPoint(_) => Some(GeomType::Point),
+        Geometry::Line(_) | Geometry::LineString(_) | Geometry::MultiLineString(_) => {
+            Some(GeomType::Linestring)
+        }
+        Geometry::Polygon(_)
+        | Geometry::MultiPolygon(_)
+        | Geometry::Rect(_)
+        | Geometry::Triangle(_) => Some(GeomType::Polygon),
+        Geometry::GeometryCollection(collection) => {
+            let mut types = collection.iter().map(geo_geom_type);
+            let first = types.next()??;
+            types.all(|t| t == Some(first)).then_some(first)
+        }
+    }
+}
+
+fn convert<T: CoordNum>(
+    geometry: &DecodedGeometry,
+    coord: impl Fn([i32; 2]) -> Coord<T>,
+) -> Option<Geometry<T>> {
+    let line = |line: &Vec<[i32; 2]>| LineString(line.iter().copied().map(&coord).collect());
+    let polygon = |rings: &Vec<Vec<[i32; 2]>>| {
+        let mut rings = rings.iter().map(line);
+        let exterior = rings.next().unwrap_or_else(|| LineString(vec![]));
+        Polygon::new(exterior, rings.collect())
+    };
+
+    let geometry = match geometry {
+        DecodedGeometry::Points(points) => match &points[..] {
+            [point] => Geometry::Point(Point(coord(*point))),
+            _ => Geometry::MultiPoint(MultiPoint(
+                points.iter().map(|&p| Point(coord(p))).collect(),
+            )),
+        },
+        DecodedGeometry::LineStrings(lines) => match &lines[..] {
+            [single] => Geometry::LineString(line(single)),
+            _ => Geometry::MultiLineString(MultiLineString(lines.iter().map(line).collect())),
+        },
+        DecodedGeometry::Polygons(polygons) => match &polygons[..] {
+            [single] => Geometry::Polygon(polygon(single)),
+            _ => Geometry::MultiPolygon(MultiPolygon(polygons.iter().map(polygon).collect())),
+        },
+        DecodedGeometry::Unknown(_) => return None,
+    };
+    Some(geometry)
+}
+
+fn map_geometry<T: CoordNum, U: CoordNum>(
+    geometry: &Geometry<T>,
+    f: &impl Fn(Coord<T>) -> Coord<U>,
+) -> Geometry<U> {
+    let line = |line: &LineString<T>| LineString(line.0.iter().copied().map(f).collect());
+    let polygon =
+        |p: &Polygon<T>| Polygon::new(line(p.exterior()), p.interiors().iter().map(line).collect());
+    match geometry {
+        Geometry::Point(p) => Geometry::Point(Point(f(p.0))),
+        Geometry::MultiPoint(points) => {
+            Geometry::MultiPoint(MultiPoint(points.iter().map(|p| Point(f(p.0))).collect()))
+        }
+        Geometry::Line(l) => Geometry::Line(geo_types::Line::new(f(l.start), f(l.end))),
+        Geometry::LineString(l) => Geometry::LineString(line(l)),
+        Geometry::MultiLineString(lines) => {
+            Geometry::MultiLineString(MultiLineString(lines.iter().map(line).collect()))
+        }
+        Geometry::Polygon(p) => Geometry::Polygon(polygon(p)),
+        Geometry::MultiPolygon(polygons) => {
+            Geometry::MultiPolygon(MultiPolygon(polygons.iter().map(polygon).collect()))
+        }
+        Geometry::Rect(rect) => Geometry::Polygon(polygon(&rect.to_polygon())),
+        Geometry::Triangle(triangle) => Geometry::Polygon(polygon(&triangle.to_polygon())),
+        Geometry::GeometryCollection(collection) => {
+            Geometry::GeometryCollection(collection.iter().map(|g| map_geometry(g, f)).collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clip::Clipper;
+    use crate::geometry::{GeometryDecoder, calculate_signed_area};
+    use crate::validate::validate;
+    use crate::vector_tile::Tile;
+    use crate::vector_tile::tile::{Feature, Layer};
+    use geo_types::{GeometryCollection, Rect, coord, line_string, point, polygon};
+
+    #[test]
+    fn test_to_geo() {
+        let points = DecodedGeometry::Points(vec![[1, 2]]);
+        assert_eq!(to_geo(&points), Some(Geometry::Point(point!(x: 1, y: 2))));
+
+        let lines = DecodedGeometry::LineStrings(vec![vec![[0, 0], [1, 1]], vec![[2, 2], [3, 3]]]);
+        assert_eq!(
+            to_geo_f64(&lines),
+            Some(Geometry::MultiLineString(MultiLineString(vec![
+                line_string![(x: 0., y: 0.), (x: 1., y: 1.)],
+                line_string![(x: 2., y: 2.), (x: 3., y: 3.)],
+            ])))
+        );
+
+        let polygons = DecodedGeometry::Polygons(vec![vec![
+            vec![[0, 0], [10, 0], [10, 10], [0, 10]],
+            vec![[2, 2], [2, 4], [4, 4], [4, 2]],
+        ]]);
+        let Some(Geometry::Polygon(polygon)) = to_geo(&polygons) else {
+            panic!("expected a polygon");
+        };
+        assert_eq!(polygon.exterior().0.len(), 5); // closed by geo_types
+        assert_eq!(polygon.interiors().len(), 1);
+
+        assert_eq!(to_geo(&DecodedGeometry::Unknown(vec![9, 0, 0])), None);
+    }
+
+    #[test]
+    fn test_lnglat_roundtrip() {
+        let geometry = DecodedGeometry::LineStrings(vec![vec![[0, 0], [4096, 4096]]]);
+        let Some(lnglat) = to_geo_lnglat(&geometry, (0, 0, 0), 4096) else {
+            panic!("expected a geometry");
+        };
+        let Geometry::LineString(line) = &lnglat else {
+            panic!("expected a line string");
+        };
+        assert!((line.0[0].x + 180.0).abs() < 1e-9);
+        assert!((line.0[1].y + 85.0511287798066).abs() < 1e-9);
+
+        let tile = lnglat_to_tile_geometry(&lnglat, (0, 0, 0), 4096);
+        assert_eq!(tile, to_geo(&geometry).unwrap());
+    }
+
+    #[test]
+    fn test_add_geo_far_outside() {
+        let lnglat = Geometry::LineString(line_string![(x: 0.0, y: 0.0), (x: 170.0, y: 0.0)]);
+        let tile = (24, 1 << 23, (1 << 23) - 1);
+        let geometry = lnglat_to_tile_geometry(&lnglat, tile, 4096);
+
+        let mut encoder = GeometryEncoder::with_clipper(Clipper::new(4096, 64));
+        assert_eq!(encoder.add_geo(&geometry), Some(GeomType::Linestring));
+        let geometry = encoder.into_vec();
+        let lines = GeometryDecoder::new(&geometry)
+            .decode_linestrings()
+            .unwrap();
+        assert_eq!(lines, [vec![[0, 4096], [4160, 4096]]]);
+    }
+
+    #[test]
+    fn test_add_geo() {
+        // counter-clockwise exterior rings are reversed
+        let multi = MultiPolygon(vec![
+            polygon![(x: 0, y: 0), (x: 0, y: 10), (x: 10, y: 10), (x: 10, y: 0)],
+            polygon!(
+                exterior: [(x: 20, y: 20), (x: 40, y: 20), (x: 40, y: 40), (x: 20, y: 40)],
+                interiors: [[(x: 25, y: 25), (x: 30, y: 25), (x: 30, y: 30), (x: 25, y: 30)]],
+            ),
+        ]);
+        let mut encoder = GeometryEncoder::new();
+        assert_eq!(
+            encoder.add_geo(&Geometry::MultiPolygon(multi)),
+            Some(GeomType::Polygon)
+        );
+        let geometry = encoder.into_vec();
+        let polygons = GeometryDecoder::new(&geometry).decode_polygons().unwrap();
+        assert_eq!(polygons.len(), 2);
+        assert_eq!(polygons[1].len(), 2);
+        for polygon in &polygons {
+            assert!(calculate_signed_area(&polygon[0]) > 0.0);
+            assert!(polygon[1..].iter().all(|r| calculate_signed_area(r) < 0.0));
+        }
+
+        let mut encoder = GeometryEncoder::new();
+        let rect = Rect::new(coord! { x: 0, y: 0 }, coord! { x: 5, y: 5 });
+        assert_eq!(
+            encoder.add_geo(&Geometry::Rect(rect)),
+            Some(GeomType::Polygon)
+        );
+        assert!(!encoder.into_vec().is_empty());
+
+        // points of a collection are added as a single MoveTo command
+        let points = GeometryCollection(vec![
+            Geometry::Point(point!(x: 1, y: 1)),
+            Geometry::MultiPoint(vec![point!(x: 2, y: 2), point!(x: 3, y: 3)].into()),
+            Geometry::GeometryCollection(GeometryCollection(vec![Geometry::Point(
+                point!(x: 4, y: 4),
+            )])),
+        ]);
+        let mut encoder = GeometryEncoder::new();
+        assert_eq!(
+            encoder.add_geo(&Geometry::GeometryCollection(points)),
+            Some(GeomType::Point)
+        );
+        let geometry = encoder.into_vec();
+        assert_eq!(
+            GeometryDecoder::new(&geometry).decode_points().unwrap(),
+            [[1, 1], [2, 2], [3, 3], [4, 4]]
+        );
+        let tile = Tile {
+            layers: vec![Layer {
+                version: 2,
+                name: "points".to_string(),
+                features: vec![Feature {
+                    id: None,
+                    tags: vec![],
+                    r#type: Some(GeomType::Point as i32),
+                    geometry,
+                }],
+                keys: vec![],
+                values: vec![],
+                extent: Some(4096),
+            }],
+        };
+        assert_eq!(validate(&tile), []);
+
+        let mixed = GeometryCollection(vec![
+            Geometry::Point(point!(x: 1, y: 1)),
+            Geometry::LineString(line_string![(x: 0, y: 0), (x: 1, y: 1)]),
+        ]);
+        let mut encoder = GeometryEncoder::new();
+        assert_eq!(encoder.add_geo(&Geometry::GeometryCollection(mixed)), None);
+        assert!(encoder.into_vec().is_empty());
+    }
+}
@@ -0,0 +1,219 @@
+//! Export of decoded layers to GeoJSON in geographic coordinates.
+
+use serde_json::{Map, Value as JsonValue, json};
+
+use crate::TileZXY;
+use crate::error::Result;
+use crate::feature::decode_layer;
+use crate::geometry::DecodedGeometry;
+use crate::tag::Value;
+use crate::vector_tile::tile::Layer;
+use crate::webmercator::tile_coord_to_lnglat;
+
+/// Converts a layer of the given tile to a GeoJSON `FeatureCollection` in (lng, lat).
+///
+/// Features with the `UNKNOWN` geometry type are exported with a `null` geometry.
+pub fn layer_to_feature_collection(layer: &Layer, tile: TileZXY) -> Result<JsonValue> {
+    let extent = layer.extent.unwrap_or(4096);
+
+    let mut features = Vec::with_capacity(layer.features.len());
+    for feature in decode_layer(layer) {
+        let feature = feature?;
+        let properties: Map<String, JsonValue> = feature
+            .properties
+            .iter()
+            .map(|(key, value)| (key.to_string(), value_to_json(value)))
+            .collect();
+
+        let mut object = Map::new();
+        object.insert("type".to_string(), json!("Feature"));
+        if let Some(id) = feature.id {
+            object.insert("id".to_string(), json!(id));
+        }
+        object.insert(
+            "geometry".to_string(),
+            geometry_to_json(&feature.geometry, tile, extent),
+        );
+        object.insert("properties".to_string(), JsonValue::Object(properties));
+        features.push(JsonValue::Object(object));
+    }
+
+    Ok(json!({
+        "type": "FeatureCollection",
+        "features": features,
+    }))
+}
+
+/// Converts a decoded geometry in tile-local coordinates to a GeoJSON geometry in (lng, lat).
+///
+/// Single-part geometries become `Point`, `LineString` or `Polygon`; multi-part geometries become
+/// their `Multi*` counterparts. Rings are closed, and since the Y axis is flipped, clockwise MVT
+/// exterior rings become counter-clockwise as recommended by RFC 7946. Unknown geometries
+/// become `null`.
+pub fn geometry_to_json(geometry: &DecodedGeometry, tile: TileZXY, extent: u32) -> JsonValue {
+    let position = |&coord: &[i32; 2]| {
+        let (lng, lat) = tile_coord_to_lnglat(tile, extent, coord);
+        json!([lng, lat])
+    };
+    let line = |line: &Vec<[i32; 2]>| JsonValue::Array(line.iter().map(position).collect());
+    let ring = |ring: &Vec<[i32; 2]>| {
+        let mut positions: Vec<_> = ring.iter().map(position).collect();
+        if ring.len() > 1 && ring.first() != ring.last() {
+            positions.push(position(&ring[0]));
+        }
+        JsonValue::Array(positions)
+    };
+    let polygon =
+        |polygon: &Vec<Vec<[i32; 2]>>| JsonValue::Array(polygon.iter().map(ring).collect());
+
+    match geometry {
+        DecodedGeometry::Points(points) => match &points[..] {
+            [point] => json!({"type": "Point", "coordinates": position(point)}),
+            _ => json!({
+                "type": "MultiPoint",
+                "coordinates": points.iter().map(position).collect::<Vec<_>>(),
+            }),
+        },
+        DecodedGeometry::LineStrings(lines) => match &lines[..] {
+            [single] => json!({"type": "LineString", "coordinates": line(single)}),
+            _ => json!({
+                "type": "MultiLineString",
+                "coordinates": lines.iter().map(line).collect::<Vec<_>>(),
+            }),
+        },
+        DecodedGeometry::Polygons(polygons) => match &polygons[..] {
+            [single] => json!({"type": "Polygon", "coordinates": polygon(single)}),
+            _ => json!({
+                "type": "MultiPolygon",
+                "coordinates": polygons.iter().map(polygon).collect::<Vec<_>>(),
+            }),
+        },
+        DecodedGeometry::Unknown(_) => JsonValue::Null,
+    }
+}
+
+/// Converts a tag value to a JSON value. Non-finite floats become `null`.
+pub fn value_to_json(value: &Value) -> JsonValue {
+    match value {
+        Value::String(s) => json!(s),
+        Value::Float(bytes) => json!(f32::from_ne_bytes(*bytes)),
+        Value::Double(bytes) => json!(f64::from_ne_bytes(*bytes)),
+        Value::Int(v) | Value::SInt(v) => json!(v),
+        Value::Uint(v) => json!(v),
+        Value::Bool(v) => json!(v),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::GeometryEncoder;
+    use crate::tag::TagsEncoder;
+    use crate::vector_tile::tile::{Feature, GeomType};
+
+    fn assert_position(value: &JsonValue, lng: f64, lat: f64) {
+        let [x, y] = [&value[0], &value[1]].map(|v| v.as_f64().unwrap());
+        assert!((x - lng).abs() < 1e-9, "{} != {}", x, lng);
+        assert!((y - lat).abs() < 1e-9, "{} != {}", y, lat);
+    }
+
+    #[test]
+    fn test_layer_to_feature_collection() {
+        let mut tags_enc = TagsEncoder::new();
+        let mut features = Vec::new();
+
+        let mut geom_enc = GeometryEncoder::new();
+        geom_enc.add_points([[2048, 2048]]);
+        tags_enc.add("name", "null island");
+        tags_enc.add("rank", -3);
+        tags_enc.add("visible", true);
+        features.push(Feature {
+            id: Some(7),
+            tags: tags_enc.take_tags(),
+            r#type: Some(GeomType::Point as i32),
+            geometry: geom_enc.into_vec(),
+        });
+
+        let mut geom_enc = GeometryEncoder::new();
+        geom_enc.add_linestring([[0, 0], [4096, 4096]]);
+        geom_enc.add_linestring([[0, 4096], [4096, 0]]);
+        features.push(Feature {
+            id: None,
+            tags: vec![],
+            r#type: Some(GeomType::Linestring as i32),
+            geometry: geom_enc.into_vec(),
+        });
+
+        let mut geom_enc = GeometryEncoder::new();
+        geom_enc.add_ring([[0, 0], [2048, 0], [2048, 2048], [0, 2048]]);
+        features.push(Feature {
+            id: None,
+            tags: vec![],
+            r#type: Some(GeomType::Polygon as i32),
+            geometry: geom_enc.into_vec(),
+        });
+
+        features.push(Feature {
+            id: None,
+            tags: vec![],
+            r#type: None,
+            geometry: vec![],
+        });
+
+        let (keys, values) = tags_enc.into_keys_and_values();
+        let layer = Layer {
+            version: 2,
+            name: "test".to_string(),
+            features,
+            keys,
+            values,
+            extent: Some(4096),
+        };
+
+        let fc = layer_to_feature_collection(&layer, (0, 0, 0)).unwrap();
+        assert_eq!(fc["type"], "FeatureCollection");
+        let features = fc["features"].as_array().unwrap();
+        assert_eq!(features.len(), 4);
+
+        let point = &features[0];
+        assert_eq!(point["id"], 7);
+        assert_eq!(point["geometry"]["type"], "Point");
+        assert_position(&point["geometry"]["coordinates"], 0.0, 0.0);
+        assert_eq!(
+            point["properties"],
+            json!({"name": "null island", "rank": -3, "visible": true})
+        );
+
+        let lines = &features[1];
+        assert!(lines.get("id").is_none());
+        assert_eq!(lines["geometry"]["type"], "MultiLineString");
+        assert_position(
+            &lines["geometry"]["coordinates"][0][0],
+            -180.0,
+            85.0511287798066,
+        );
+        assert_position(
+            &lines["geometry"]["coordinates"][0][1],
+            180.0,
+            -85.0511287798066,
+        );
+
+        let polygon = &features[2];
+        assert_eq!(polygon["geometry"]["type"], "Polygon");
+        let ring = polygon["geometry"]["coordinates"][0].as_array().unwrap();
+        assert_eq!(ring.len(), 5);
+        assert_eq!(ring[0], ring[4]);
+        assert_position(&ring[2], 0.0, 0.0);
+
+        assert_eq!(features[3]["geometry"], JsonValue::Null);
+    }
+
+    #[test]
+    fn test_value_to_json() {
+        assert_eq!(value_to_json(&Value::Uint(u64::MAX)), json!(u64::MAX));
+        assert_eq!(value_to_json(&Value::Int(-1)), json!(-1));
+        assert_eq!(value_to_json(&Value::from(1.5f32)), json!(1.5));
+        assert_eq!(value_to_json(&Value::from(f64::NAN)), JsonValue::Null);
+        assert_eq!(value_to_json(&Value::from("a")), json!("a"));
+    }
+}
@@ -0,0 +1,428 @@
+//! Import of GeoJSON features in geographic coordinates into an MVT layer.
+
+use serde_json::{Map, Value as JsonValue};
+
+use crate::TileZXY;
+use crate::clip::Clipper;
+use crate::error::{Error, Result};
+use crate::geometry::GeometryEncoder;
+use crate::tag::{TagsEncoder, Value};
+use crate::vector_tile::tile::{Feature, GeomType, Layer};
+use crate::webmercator::lnglat_to_tile_coord;
+
+/// How nested objects and arrays in GeoJSON properties are converted to tags.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NestedProperties {
+    /// Flattens nested members into separate tags, joining the keys (or array indices) with the separator.
+    ///
+    /// e.g. `{"a": {"b": 1, "c": [true]}}` becomes `a.b = 1` and `a.c.0 = true` with separator `"."`.
+    Flatten { separator: String },
+    /// Serializes nested members to JSON strings.
+    Stringify,
+}
+
+/// Options for [`feature_collection_to_layer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportOptions {
+    /// Extent of the output layer.
+    pub extent: u32,
+    /// Buffer around the tile (in tile units) to which geometries are clipped.
+    pub buffer: u32,
+    /// Conversion of nested properties.
+    pub nested: NestedProperties,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            extent: 4096,
+            buffer: 64,
+            nested: NestedProperties::Stringify,
+        }
+    }
+}
+
+/// Builds a layer for the given tile from GeoJSON in WGS84 (lng, lat).
+///
+/// The input may be a `FeatureCollection`, a single `Feature`, or an array of features.
+/// Geometries are projected to Web Mercator, scaled into tile space, clipped to the buffered tile,
+/// and encoded with the winding order required by MVT. Features with a `null` geometry or whose
+/// geometry lies entirely outside the tile are skipped. `null` properties are omitted.
+pub fn feature_collection_to_layer(
+    name: &str,
+    geojson: &JsonValue,
+    tile: TileZXY,
+    options: &ImportOptions,
+) -> Result<Layer> {
+    let features = match geojson.get("type").and_then(JsonValue::as_str) {
+        Some("FeatureCollection") => geojson
+            .get("features")
+            .and_then(JsonValue::as_array)
+            .ok_or_else(|| invalid("FeatureCollection without features array"))?
+            .as_slice(),
+        Some("Feature") => std::slice::from_ref(geojson),
+        _ => geojson
+            .as_array()
+            .ok_or_else(|| invalid("expected FeatureCollection, Feature or array of features"))?
+            .as_slice(),
+    };
+
+    let clipper = Clipper::new(options.extent, options.buffer);
+    let project = |position: &JsonValue| -> Result<[i32; 2]> {
+        let [lng, lat] = parse_position(position)?;
+        Ok(lnglat_to_tile_coord(tile, options.extent, lng, lat))
+    };
+
+    let mut tags_enc = TagsEncoder::new();
+    let mut out_features = Vec::with_capacity(features.len());
+    for feature in features {
+        if feature.get("type").and_then(JsonValue::as_str) != Some("Feature") {
+            return Err(invalid("expected Feature"));
+        }
+        let geometry = match feature.get("geometry") {
+            None | Some(JsonValue::Null) => continue,
+            Some(geometry) => geometry,
+        };
+
+        let mut geom_enc = GeometryEncoder::with_clipper(clipper);
+        let geom_type = encode_geometry(&mut geom_enc, geometry, &project)?;
+        let geometry = geom_enc.into_vec();
+        if geometry.is_empty() {
+            continue;
+        }
+
+        if let Some(properties) = feature.get("properties").and_then(JsonValue::as_object) {
+            add_properties(&mut tags_enc, properties, &options.nested);
+        }
+
+        out_features.push(Feature {
+            id: feature.get("id").and_then(JsonValue::as_u64),
+            tags: tags_enc.take_tags(),
+            r#type: Some(geom_type as i32),
+            geometry,
+        });
+    }
+
+    let (keys, values) = tags_enc.into_keys_and_values();
+    Ok(Layer {
+        version: 2,
+        name: name.to_string(),
+        features: out_features,
+        keys,
+        values,
+        extent: Some(options.extent),
+    })
+}
+
+fn encode_geometry(
+    geom_enc: &mut GeometryEncoder,
+    geometry: &JsonValue,
+    project: &impl Fn(&JsonValue) -> Result<[i32; 2]>,
+) -> Result<GeomType> {
+    let geom_type = geometry
+        .get("type")
+        .and_then(JsonValue::as_str)
+        .ok_or_else(|| invalid("geometry without type"))?;
+    let coordinates = geometry
+        .get("coordinates")
+        .ok_or_else(|| invalid("geometry without coordinates"))?;
+
+    let path = |value: &JsonValue| -> Result<Vec<[i32; 2]>> {
+        as_array(value)?.iter().map(project).collect()
+    };
+    let rings = |value: &JsonValue| -> Result<Vec<Vec<[i32; 2]>>> {
+        as_array(value)?.iter().map(path).collect()
+    };
+
+    match geom_type {
+        "Point" => {
+            geom_enc.add_points([project(coordinates)?]);
+            Ok(GeomType::Point)
+        }
+        "MultiPoint" => {
+            geom_enc.add_points(path(coordinates)?);
+            Ok(GeomType::Point)
+        }
+        "LineString" => {
+            geom_enc.add_linestring(path(coordinates)?);
+            Ok(GeomType::Linestring)
+        }
+        "MultiLineString" => {
+            for line in rings(coordinates)? {
+                geom_enc.add_linestring(line);
+            }
+            Ok(GeomType::Linestring)
+        }
+        "Polygon" => {
+            geom_enc.add_polygon(rings(coordinates)?);
+            Ok(GeomType::Polygon)
+        }
+        "MultiPolygon" => {
+            for polygon in as_array(coordinates)? {
+                geom_enc.add_polygon(rings(polygon)?);
+            }
+            Ok(GeomType::Polygon)
+        }
+        other => Err(invalid(&format!("unsupported geometry type {}", other))),
+    }
+}
+
+fn add_properties(
+    tags_enc: &mut TagsEncoder,
+    properties: &Map<String, JsonValue>,
+    nested: &NestedProperties,
+) {
+    for (key, value) in properties {
+        add_property(tags_enc, key, value, nested);
+    }
+}
+
+fn add_property(
+    tags_enc: &mut TagsEncoder,
+    key: &str,
+    value: &JsonValue,
+    nested: &NestedProperties,
+) {
+    match (value, nested) {
+        (JsonValue::Null, _) => {}
+        (JsonValue::Bool(b), _) => tags_enc.add(key, *b),
+        (JsonValue::Number(n), _) => {
+            if let Some(u) = n.as_u64() {
+                tags_enc.add(key, u);
+            } else if let Some(i) = n.as_i64() {
+                tags_enc.add(key, i);
+            } else if let Some(f) = n.as_f64() {
+                tags_enc.add(key, f);
+            }
+        }
+        (JsonValue::String(s), _) => tags_enc.add(key, s.as_str()),
+        (JsonValue::Array(_) | JsonValue::Object(_), NestedProperties::Stringify) => {
+            tags_enc.add(key, Value::String(value.to_string()))
+        }
+        (JsonValue::Array(items), NestedProperties::Flatten { separator }) => {
+            for (i, item) in items.iter().enumerate() {
+                add_property(
+                    tags_enc,
+                    &format!("{}{}{}", key, separator, i),
+                    item,
+                    nested,
+                );
+            }
+        }
+        (JsonValue::Object(members), NestedProperties::Flatten { separator }) => {
+            for (k, v) in members {
+                add_property(tags_enc, &format!("{}{}{}", key, separator, k), v, nested);
+            }
+        }
+    }
+}
+
+fn parse_position(value: &JsonValue) -> Result<[f64; 2]> {
+    match value.as_array().map(Vec::as_slice) {
+        Some([lng, lat, ..]) => match (lng.as_f64(), lat.as_f64()) {
+            (Some(lng), Some(lat)) => Ok([lng, lat]),
+            _ => Err(invalid("position must contain numbers")),
+        },
+        _ => Err(invalid("position must have at least two elements")),
+    }
+}
+
+#[inline]
+fn as_array(value: &JsonValue) -> Result<&Vec<JsonValue>> {
+    value
+        .as_array()
+        .ok_or_else(|| invalid("coordinates must be an array"))
+}
+
+#[inline]
+fn invalid(reason: &str) -> Error {
+    Error::InvalidGeoJson {
+        reason: reason.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::geometry::{GeometryDecoder, calculate_signed_area};
+    use crate::tag::TagsDecoder;
+
+    #[test]
+    fn test_feature_collection_to_layer() {
+        let geojson = json!({
+            "type": "FeatureCollection",
+            "features": [
+                {
+                    "type": "Feature",
+                    "id": 1,
+                    "geometry": {"type": "Point", "coordinates": [0.0, 0.0]},
+                    "properties": {"name": "null island", "n": -1, "x": 1.5, "nothing": null}
+                },
+                {
+                    "type": "Feature",
+                    "geometry": {"type": "LineString", "coordinates": [[-90.0, 0.0], [90.0, 0.0]]},
+                    "properties": {}
+                },
+                {
+                    "type": "Feature",
+                    "geometry": {
+                        "type": "Polygon",
+                        // counter-clockwise exterior in (lng, lat), as in RFC 7946
+                        "coordinates": [[[-90.0, -45.0], [90.0, -45.0], [90.0, 45.0], [-90.0, 45.0], [-90.0, -45.0]]]
+                    },
+                    "properties": null
+                },
+                {"type": "Feature", "geometry": null, "properties": {}}
+            ]
+        });
+        let layer =
+            feature_collection_to_layer("test", &geojson, (0, 0, 0), &ImportOptions::default())
+                .unwrap();
+        assert_eq!(layer.name, "test");
+        assert_eq!(layer.extent, Some(4096));
+        assert_eq!(layer.features.len(), 3);
+
+        let point = &layer.features[0];
+        assert_eq!(point.id, Some(1));
+        assert_eq!(point.r#type, Some(GeomType::Point as i32));
+        let points = GeometryDecoder::new(&point.geometry)
+            .decode_points()
+            .unwrap();
+        assert_eq!(points, [[2048, 2048]]);
+        let tags = TagsDecoder::new(&layer.keys, &layer.values)
+            .decode(&point.tags)
+            .unwrap();
+        assert_eq!(
+            tags,
+            [
+                ("n", Value::SInt(-1)),
+                ("name", Value::from("null island")),
+                ("x", Value::from(1.5f64)),
+            ]
+        );
+
+        let line = &layer.features[1];
+        let lines = GeometryDecoder::new(&line.geometry)
+            .decode_linestrings()
+            .unwrap();
+        assert_eq!(lines, [vec![[1024, 2048], [3072, 2048]]]);
+
+        let polygon = &layer.features[2];
+        let polygons = GeometryDecoder::new(&polygon.geometry)
+            .decode_polygons()
+            .unwrap();
+        assert_eq!(polygons.len(), 1);
+        assert_eq!(polygons[0].len(), 1);
+        assert_eq!(polygons[0][0].len(), 4);
+    }
+
+    #[test]
+    fn test_clipping() {
+        // a line crossing the tile (1, 0, 0), which covers lng [-180, 0] and lat [0, 85]
+        let geojson = json!({
+            "type": "Feature",
+            "geometry": {"type": "LineString", "coordinates": [[-90.0, 45.0], [90.0, 45.0]]},
+            "properties": {}
+        });
+        let options = ImportOptions {
+            buffer: 0,
+            ..Default::default()
+        };
+        let layer = feature_collection_to_layer("test", &geojson, (1, 0, 0), &options).unwrap();
+        let lines = GeometryDecoder::new(&layer.features[0].geometry)
+            .decode_linestrings()
+            .unwrap();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0][0][0], 2048);
+        assert_eq!(lines[0][1][0], 4096);
+
+        // entirely outside
+        let layer = feature_collection_to_layer("test", &geojson, (1, 0, 1), &options).unwrap();
+        assert!(layer.features.is_empty());
+    }
+
+    #[test]
+    fn test_far_outside_coordinates() {
+        // the tile just north-east of (0, 0) at z20, with vertices far outside of it
+        let tile = (20, 1 << 19, (1 << 19) - 1);
+        let geojson = json!([
+            {
+                "type": "Feature",
+                "geometry": {"type": "LineString", "coordinates": [[0.0, 0.0], [170.0, 0.0]]},
+                "properties": {}
+            },
+            {
+                "type": "Feature",
+                "geometry": {
+                    "type": "Polygon",
+                    "coordinates": [[[-170.0, -80.0], [170.0, -80.0], [170.0, 80.0], [-170.0, 80.0], [-170.0, -80.0]]]
+                },
+                "properties": {}
+            }
+        ]);
+        let layer =
+            feature_collection_to_layer("test", &geojson, tile, &ImportOptions::default()).unwrap();
+        assert_eq!(layer.features.len(), 2);
+
+        let lines = GeometryDecoder::new(&layer.features[0].geometry)
+            .decode_linestrings()
+            .unwrap();
+        assert_eq!(lines, [vec![[0, 4096], [4160, 4096]]]);
+        let polygons = GeometryDecoder::new(&layer.features[1].geometry)
+            .decode_polygons()
+            .unwrap();
+        assert_eq!(polygons.len(), 1);
+        assert_eq!(calculate_signed_area(&polygons[0][0]), 4224.0 * 4224.0);
+    }
+
+    #[test]
+    fn test_nested_properties() {
+        let geojson = json!([{
+            "type": "Feature",
+            "geometry": {"type": "Point", "coordinates": [0.0, 0.0]},
+            "properties": {"a": {"b": 1, "c": [true, "x"]}}
+        }]);
+
+        let options = ImportOptions {
+            nested: NestedProperties::Flatten {
+                separator: ".".to_string(),
+            },
+            ..Default::default()
+        };
+        let layer = feature_collection_to_layer("test", &geojson, (0, 0, 0), &options).unwrap();
+        let tags = TagsDecoder::new(&layer.keys, &layer.values)
+            .decode(&layer.features[0].tags)
+            .unwrap();
+        assert_eq!(
+            tags,
+            [
+                ("a.b", Value::Uint(1)),
+                ("a.c.0", Value::Bool(true)),
+                ("a.c.1", Value::from("x")),
+            ]
+        );
+
+        let layer =
+            feature_collection_to_layer("test", &geojson, (0, 0, 0), &ImportOptions::default())
+                .unwrap();
+        let tags = TagsDecoder::new(&layer.keys, &layer.values)
+            .decode(&layer.features[0].tags)
+            .unwrap();
+        assert_eq!(tags, [("a", Value::from(r#"{"b":1,"c":[true,"x"]}"#))]);
+    }
+
+    #[test]
+    fn test_invalid_geojson() {
+        let options = ImportOptions::default();
+        for geojson in [
+            json!({"type": "Point", "coordinates": [0.0, 0.0]}),
+            json!([{"type": "Feature", "geometry": {"type": "Point", "coordinates": [0.0]}}]),
+            json!([{"type": "Feature", "geometry": {"type": "GeometryCollection", "geometries": []}}]),
+        ] {
+            let result = feature_collection_to_layer("test", &geojson, (0, 0, 0), &options);
+            assert!(matches!(result, Err(Error::InvalidGeoJson { .. })));
+        }
+    }
+}
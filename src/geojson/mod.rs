@@ -0,0 +1,7 @@
+//! Conversion between MVT layers and GeoJSON.
+
+pub mod export;
+pub mod import;
+
+pub use export::*;
+pub use import::*;
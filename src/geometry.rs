@@ -1,8 +1,12 @@
 //! Geometry encoder for MVT.
 
-const GEOM_COMMAND_MOVE_TO: u32 = 1;
-const GEOM_COMMAND_LINE_TO: u32 = 2;
-const GEOM_COMMAND_CLOSE_PATH: u32 = 7;
+use crate::clip::Clipper;
+use crate::error::{Error, Result};
+use crate::vector_tile::tile::GeomType;
+
+pub(crate) const GEOM_COMMAND_MOVE_TO: u32 = 1;
+pub(crate) const GEOM_COMMAND_LINE_TO: u32 = 2;
+pub(crate) const GEOM_COMMAND_CLOSE_PATH: u32 = 7;
 
 const GEOM_COMMAND_MOVE_TO_WITH_COUNT1: u32 = 1 << 3 | GEOM_COMMAND_MOVE_TO;
 const GEOM_COMMAND_CLOSE_PATH_WITH_COUNT1: u32 = 1 << 3 | GEOM_COMMAND_CLOSE_PATH;
@@ -12,6 +16,7 @@ pub struct GeometryEncoder {
     buf: Vec<u32>,
     prev_x: i32,
     prev_y: i32,
+    clipper: Option<Clipper>,
 }
 
 impl GeometryEncoder {
@@ -22,6 +27,19 @@ impl GeometryEncoder {
             buf: Vec::new(),
             prev_x: 0,
             prev_y: 0,
+            clipper: None,
+        }
+    }
+
+    /// Creates an encoder that clips every geometry to the given clip box before encoding.
+    ///
+    /// Line strings that leave and re-enter the box are split into multiple parts,
+    /// and rings that degenerate after clipping are dropped.
+    #[inline]
+    pub fn with_clipper(clipper: Clipper) -> Self {
+        Self {
+            clipper: Some(clipper),
+            ..Self::new()
         }
     }
 
@@ -33,6 +51,14 @@ impl GeometryEncoder {
 
     /// Adds points.
     pub fn add_points(&mut self, iterable: impl IntoIterator<Item = [i32; 2]>) {
+        if let Some(clipper) = self.clipper {
+            self.add_points_inner(clipper.clip_points(iterable))
+        } else {
+            self.add_points_inner(iterable)
+        }
+    }
+
+    fn add_points_inner(&mut self, iterable: impl IntoIterator<Item = [i32; 2]>) {
         let mut iter = iterable.into_iter();
         let Some([first_x, first_y]) = iter.next() else {
             return;
@@ -63,14 +89,65 @@ impl GeometryEncoder {
 
     /// Adds a line string.
     pub fn add_linestring(&mut self, iterable: impl IntoIterator<Item = [i32; 2]>) {
-        self.add_path(iterable, false)
+        if let Some(clipper) = self.clipper {
+            for part in clipper.clip_linestring(iterable) {
+                self.add_path(part, false)
+            }
+        } else {
+            self.add_path(iterable, false)
+        }
     }
 
     /// Adds a polygon ring.
     ///
     /// A polygon consists of one exterior ring (clockwise) and optionally one or more interior rings (counter-clockwise).
     pub fn add_ring(&mut self, iterable: impl IntoIterator<Item = [i32; 2]>) {
-        self.add_path(iterable, true)
+        if let Some(clipper) = self.clipper {
+            if let Some(ring) = clipper.clip_ring(iterable) {
+                self.add_path(ring, true)
+            }
+        } else {
+            self.add_path(iterable, true)
+        }
+    }
+
+    /// Adds a polygon consisting of an exterior ring followed by zero or more interior rings (holes).
+    ///
+    /// Unlike [`add_ring`](Self::add_ring), the winding order of each ring is corrected as needed,
+    /// so that the exterior ring is always clockwise and holes are counter-clockwise
+    /// (MVT spec 4.3.4.4). Rings may be given either open or closed. Rings with zero area are
+    /// skipped, and if the exterior ring is skipped, the whole polygon is skipped.
+    pub fn add_polygon<R>(&mut self, rings: impl IntoIterator<Item = R>)
+    where
+        R: IntoIterator<Item = [i32; 2]>,
+    {
+        let mut rings = rings.into_iter();
+        let Some(exterior) = rings.next().and_then(|ring| self.prepare_ring(ring)) else {
+            return;
+        };
+        self.add_oriented_ring(exterior, true);
+        for interior in rings {
+            if let Some(interior) = self.prepare_ring(interior) {
+                self.add_oriented_ring(interior, false);
+            }
+        }
+    }
+
+    /// Clips (if a clipper is set) and normalizes a ring for [`add_polygon`](Self::add_polygon).
+    fn prepare_ring(&self, iterable: impl IntoIterator<Item = [i32; 2]>) -> Option<Vec<[i32; 2]>> {
+        match self.clipper {
+            Some(clipper) => clipper.clip_ring(iterable),
+            None => normalize_ring(iterable.into_iter().collect()),
+        }
+    }
+
+    /// Adds a ring, reversing it if its winding order does not match the ring type.
+    fn add_oriented_ring(&mut self, mut ring: Vec<[i32; 2]>, exterior: bool) {
+        // exterior rings must have positive area, interior rings negative area
+        if (calculate_signed_area(&ring) > 0.0) != exterior {
+            ring.reverse();
+        }
+        self.add_path(ring, true)
     }
 
     /// Adds a path (line string or polygon ring).
@@ -132,216 +209,621 @@ pub enum DecodedGeometry {
     LineStrings(Vec<Vec<[i32; 2]>>),
     /// Polygon geometries (multiple polygons, each with rings).
     Polygons(Vec<Vec<Vec<[i32; 2]>>>),
+    /// Undecoded commands of a geometry with the `UNKNOWN` or an unrecognized type.
+    Unknown(Vec<u32>),
 }
 
 /// Alias for DecodedGeometry for convenience.
 pub type Geometry = DecodedGeometry;
 
-/// Utility for decoding MVT geometries.
-pub struct GeometryDecoder<'a> {
+/// A geometry command with absolute tile coordinates, as yielded by [`Commands`].
+///
+/// A command integer with a count of N yields N events; for example, a `LineTo` with
+/// count 3 yields three `LineTo` events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// Starts a new point, line string or ring at the given position.
+    MoveTo([i32; 2]),
+    /// Draws a segment from the current position to the given position.
+    LineTo([i32; 2]),
+    /// Closes the current ring.
+    ClosePath,
+    /// A command with a count of zero, whose ID is given by [`Commands::raw_command`].
+    ///
+    /// Only yielded if enabled with [`Commands::set_include_empty`].
+    Empty,
+}
+
+/// A command integer as stored in the geometry buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawCommand {
+    /// Command ID (1 = MoveTo, 2 = LineTo, 7 = ClosePath).
+    pub id: u32,
+    /// Number of times the command is repeated.
+    pub count: u32,
+    /// Index of the command integer in the geometry buffer.
+    pub offset: usize,
+}
+
+impl RawCommand {
+    /// Returns the command integer (`count << 3 | id`).
+    #[inline]
+    pub fn integer(&self) -> u32 {
+        self.count << 3 | self.id
+    }
+}
+
+/// Streaming iterator over the commands of a geometry, created by [`GeometryDecoder::commands`].
+///
+/// Coordinates are resolved to absolute positions, and nothing is allocated. Decoding stops
+/// after the first error. Commands with a count of zero are skipped unless enabled with
+/// [`Commands::set_include_empty`].
+#[derive(Debug, Clone)]
+pub struct Commands<'a> {
     buf: &'a [u32],
     pos: usize,
-    cursor_x: i32,
-    cursor_y: i32,
+    cursor: [i32; 2],
+    raw: Option<RawCommand>,
+    remaining: u32,
+    include_empty: bool,
 }
 
-impl<'a> GeometryDecoder<'a> {
-    /// Creates a new decoder for the given geometry buffer.
+impl<'a> Commands<'a> {
+    /// Creates an iterator over the commands of the given geometry buffer.
     pub fn new(buf: &'a [u32]) -> Self {
         Self {
             buf,
             pos: 0,
-            cursor_x: 0,
-            cursor_y: 0,
+            cursor: [0, 0],
+            raw: None,
+            remaining: 0,
+            include_empty: false,
         }
     }
 
-    /// Decodes points from the geometry buffer.
-    pub fn decode_points(&mut self) -> Result<Vec<[i32; 2]>, String> {
-        let mut points = Vec::new();
-
-        while self.pos < self.buf.len() {
-            let cmd_int = self.buf[self.pos];
-            self.pos += 1;
-
-            let cmd = cmd_int & 0x7;
-            let count = (cmd_int >> 3) as usize;
+    /// Sets whether commands with a count of zero are yielded as [`Command::Empty`].
+    #[inline]
+    pub fn set_include_empty(&mut self, include_empty: bool) {
+        self.include_empty = include_empty;
+    }
 
-            match cmd {
-                GEOM_COMMAND_MOVE_TO => {
-                    for _ in 0..count {
-                        let coord = self.read_coord()?;
-                        points.push(coord);
-                    }
-                }
-                _ => {
-                    return Err(format!("Unexpected command {} in point geometry", cmd));
-                }
-            }
-        }
+    /// Returns the command integer the last yielded event belongs to.
+    #[inline]
+    pub fn raw_command(&self) -> Option<RawCommand> {
+        self.raw
+    }
 
-        Ok(points)
+    /// Returns the current position in the geometry buffer.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.pos
     }
 
-    /// Decodes linestrings from the geometry buffer.
-    pub fn decode_linestrings(&mut self) -> Result<Vec<Vec<[i32; 2]>>, String> {
-        let mut linestrings = Vec::new();
+    fn fail(&mut self, err: Error) -> Option<Result<Command>> {
+        self.pos = self.buf.len();
+        self.remaining = 0;
+        Some(Err(err))
+    }
 
-        while self.pos < self.buf.len() {
-            let mut linestring = Vec::new();
+    /// Reads a coordinate pair from the buffer.
+    fn read_coord(&mut self) -> Result<[i32; 2]> {
+        let [dx, dy] = *self.buf[self.pos..]
+            .first_chunk::<2>()
+            .ok_or(Error::Truncated { offset: self.pos })?;
+        let (Some(x), Some(y)) = (
+            self.cursor[0].checked_add(unzigzag(dx)),
+            self.cursor[1].checked_add(unzigzag(dy)),
+        ) else {
+            return Err(Error::CoordinateOverflow { offset: self.pos });
+        };
+        self.pos += 2;
+        self.cursor = [x, y];
+        Ok(self.cursor)
+    }
+}
 
-            // MoveTo command
-            let cmd_int = self.buf[self.pos];
+impl Iterator for Commands<'_> {
+    type Item = Result<Command>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining == 0 {
+            let &integer = self.buf.get(self.pos)?;
+            let raw = RawCommand {
+                id: integer & 0x7,
+                count: integer >> 3,
+                offset: self.pos,
+            };
             self.pos += 1;
-
-            let cmd = cmd_int & 0x7;
-            let count = (cmd_int >> 3) as usize;
-
-            if cmd != GEOM_COMMAND_MOVE_TO {
-                return Err(format!("Expected MoveTo command, got {}", cmd));
+            self.raw = Some(raw);
+            if !matches!(
+                raw.id,
+                GEOM_COMMAND_MOVE_TO | GEOM_COMMAND_LINE_TO | GEOM_COMMAND_CLOSE_PATH
+            ) {
+                return self.fail(Error::UnknownCommand {
+                    command: raw.id,
+                    offset: raw.offset,
+                });
             }
-
-            if count != 1 {
-                return Err(format!(
-                    "MoveTo count must be 1 for linestrings, got {}",
-                    count
-                ));
+            self.remaining = raw.count;
+            if raw.count == 0 && self.include_empty {
+                return Some(Ok(Command::Empty));
             }
+        }
+        self.remaining -= 1;
 
-            let coord = self.read_coord()?;
-            linestring.push(coord);
+        let command = match self.raw.map(|raw| raw.id) {
+            Some(GEOM_COMMAND_MOVE_TO) => self.read_coord().map(Command::MoveTo),
+            Some(GEOM_COMMAND_LINE_TO) => self.read_coord().map(Command::LineTo),
+            _ => Ok(Command::ClosePath),
+        };
+        match command {
+            Ok(command) => Some(Ok(command)),
+            Err(err) => self.fail(err),
+        }
+    }
+}
 
-            // LineTo command
-            if self.pos >= self.buf.len() {
-                return Err("Unexpected end of buffer after MoveTo".to_string());
-            }
+/// How [`GeometryDecoder`] handles geometries that violate the command rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DecodePolicy {
+    /// Fails on the first violation.
+    #[default]
+    Strict,
+    /// Repairs what it can and skips broken parts, recording each violation as a warning.
+    ///
+    /// Each point of a multi-point MoveTo starts a new part, commands with a count of zero are
+    /// skipped, rings missing a ClosePath are closed implicitly, a ClosePath in a line string
+    /// closes the line, and line strings with a single point or rings with fewer than two points
+    /// are dropped. Decoding stops at a truncated buffer or unknown command, keeping what was
+    /// decoded so far.
+    Lenient,
+}
 
-            let cmd_int = self.buf[self.pos];
-            self.pos += 1;
+/// How [`GeometryDecoder::decode_polygons`] groups rings into polygons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RingClassification {
+    /// Clockwise rings start a new polygon, and counter-clockwise rings are holes of the
+    /// preceding polygon (MVT 2.x). Zero-area rings are dropped, and a leading
+    /// counter-clockwise ring is reversed into an exterior ring.
+    #[default]
+    Winding,
+    /// Holes are assigned to the exterior rings containing them regardless of winding order
+    /// (MVT 1.x, where the winding order was not specified). Zero-area rings are dropped.
+    Containment,
+}
 
-            let cmd = cmd_int & 0x7;
-            let count = (cmd_int >> 3) as usize;
+impl RingClassification {
+    /// Returns the classification suitable for a layer of the given version.
+    pub fn for_version(version: u32) -> Self {
+        if version < 2 {
+            RingClassification::Containment
+        } else {
+            RingClassification::Winding
+        }
+    }
+}
 
-            if cmd != GEOM_COMMAND_LINE_TO {
-                return Err(format!("Expected LineTo command, got {}", cmd));
-            }
+/// Utility for decoding MVT geometries.
+pub struct GeometryDecoder<'a> {
+    commands: Commands<'a>,
+    policy: DecodePolicy,
+    ring_classification: RingClassification,
+    warnings: Vec<Error>,
+}
 
-            for _ in 0..count {
-                let coord = self.read_coord()?;
-                linestring.push(coord);
-            }
+impl<'a> GeometryDecoder<'a> {
+    /// Creates a new decoder for the given geometry buffer.
+    pub fn new(buf: &'a [u32]) -> Self {
+        Self::with_policy(buf, DecodePolicy::Strict)
+    }
 
-            linestrings.push(linestring);
+    /// Creates a new decoder with the given policy for malformed geometries.
+    pub fn with_policy(buf: &'a [u32], policy: DecodePolicy) -> Self {
+        // commands with a count of zero are violations too
+        let mut commands = Commands::new(buf);
+        commands.set_include_empty(true);
+        Self {
+            commands,
+            policy,
+            ring_classification: RingClassification::default(),
+            warnings: Vec::new(),
         }
-
-        Ok(linestrings)
     }
 
-    /// Decodes polygons from the geometry buffer.
-    pub fn decode_polygons(&mut self) -> Result<Vec<Vec<Vec<[i32; 2]>>>, String> {
-        let mut polygons: Vec<Vec<Vec<[i32; 2]>>> = Vec::new();
-        let mut current_polygon: Vec<Vec<[i32; 2]>> = Vec::new();
+    /// Sets how polygon rings are grouped into polygons, see [`RingClassification::for_version`].
+    pub fn set_ring_classification(&mut self, classification: RingClassification) {
+        self.ring_classification = classification;
+    }
 
-        while self.pos < self.buf.len() {
-            let mut ring = Vec::new();
+    /// Returns the violations recovered from so far in [`DecodePolicy::Lenient`] mode.
+    ///
+    /// Each warning is the error that strict mode would have failed with.
+    pub fn warnings(&self) -> &[Error] {
+        &self.warnings
+    }
 
-            // MoveTo command
-            let cmd_int = self.buf[self.pos];
-            self.pos += 1;
+    /// Takes the recorded warnings, leaving the decoder without any.
+    pub fn take_warnings(&mut self) -> Vec<Error> {
+        std::mem::take(&mut self.warnings)
+    }
 
-            let cmd = cmd_int & 0x7;
-            let count = (cmd_int >> 3) as usize;
+    /// Returns a streaming iterator over the remaining commands, without building any `Vec`s.
+    pub fn commands(self) -> Commands<'a> {
+        let mut commands = self.commands;
+        commands.set_include_empty(false);
+        commands
+    }
 
-            if cmd != GEOM_COMMAND_MOVE_TO {
-                return Err(format!("Expected MoveTo command, got {}", cmd));
-            }
+    /// Returns the next command, along with the command integer it belongs to.
+    fn next_command(&mut self) -> Option<Result<(Command, RawCommand)>> {
+        let command = self.commands.next()?;
+        Some(command.map(|command| (command, self.commands.raw.unwrap())))
+    }
 
-            if count != 1 {
-                return Err(format!(
-                    "MoveTo count must be 1 for polygons, got {}",
-                    count
-                ));
+    /// Fails with `err` in strict mode, or records it as a warning in lenient mode.
+    fn recover(&mut self, err: Error) -> Result<()> {
+        match self.policy {
+            DecodePolicy::Strict => Err(err),
+            DecodePolicy::Lenient => {
+                // a repeated command yields the same violation for each repetition
+                if self.warnings.last() != Some(&err) {
+                    self.warnings.push(err);
+                }
+                Ok(())
             }
+        }
+    }
 
-            let coord = self.read_coord()?;
-            ring.push(coord);
+    /// Decodes points from the geometry buffer.
+    pub fn decode_points(&mut self) -> Result<Vec<[i32; 2]>> {
+        let mut points = Vec::new();
 
-            // LineTo command
-            if self.pos >= self.buf.len() {
-                return Err("Unexpected end of buffer after MoveTo".to_string());
+        while let Some(command) = self.next_command() {
+            match command {
+                Ok((Command::MoveTo(coord), _)) => points.push(coord),
+                Ok((Command::Empty, raw)) => self.recover(invalid_count(raw))?,
+                Ok((_, raw)) => self.recover(unexpected(GEOM_COMMAND_MOVE_TO, raw))?,
+                Err(err) => self.recover(err)?,
             }
+        }
 
-            let cmd_int = self.buf[self.pos];
-            self.pos += 1;
-
-            let cmd = cmd_int & 0x7;
-            let count = (cmd_int >> 3) as usize;
+        Ok(points)
+    }
 
-            if cmd != GEOM_COMMAND_LINE_TO {
-                return Err(format!("Expected LineTo command, got {}", cmd));
+    /// Decodes linestrings from the geometry buffer.
+    pub fn decode_linestrings(&mut self) -> Result<Vec<Vec<[i32; 2]>>> {
+        let mut linestrings: Vec<Vec<[i32; 2]>> = Vec::new();
+        // the line string being decoded; it still needs its LineTo command if it has one point
+        let mut line: Vec<[i32; 2]> = Vec::new();
+        let mut failed = false;
+        // whether a LineTo with a zero count was already reported for the line string
+        let mut reported = false;
+
+        while let Some(command) = self.next_command() {
+            let (command, raw) = match command {
+                Ok(command) => command,
+                Err(err) => {
+                    self.recover(err)?;
+                    failed = true;
+                    break;
+                }
+            };
+            match command {
+                Command::MoveTo(coord) => {
+                    if raw.count != 1 {
+                        self.recover(invalid_count(raw))?;
+                    } else if line.len() == 1 && !reported {
+                        self.recover(unexpected(GEOM_COMMAND_LINE_TO, raw))?;
+                    }
+                    push_linestring(&mut linestrings, std::mem::take(&mut line));
+                    line.push(coord);
+                    reported = false;
+                }
+                Command::LineTo(coord) if !line.is_empty() => line.push(coord),
+                Command::LineTo(_) => self.recover(unexpected(GEOM_COMMAND_MOVE_TO, raw))?,
+                Command::Empty => {
+                    self.recover(invalid_count(raw))?;
+                    reported = true;
+                }
+                Command::ClosePath => {
+                    let expected = match line.len() {
+                        1 => GEOM_COMMAND_LINE_TO,
+                        _ => GEOM_COMMAND_MOVE_TO,
+                    };
+                    self.recover(unexpected(expected, raw))?;
+                    if line.len() >= 2 {
+                        line.push(line[0]);
+                    }
+                }
             }
+        }
+        if line.len() == 1 && !failed && !reported {
+            self.recover(Error::Truncated {
+                offset: self.commands.offset(),
+            })?;
+        }
+        push_linestring(&mut linestrings, line);
 
-            for _ in 0..count {
-                let coord = self.read_coord()?;
-                ring.push(coord);
-            }
+        Ok(linestrings)
+    }
 
-            // ClosePath command
-            if self.pos >= self.buf.len() {
-                return Err("Unexpected end of buffer after LineTo".to_string());
+    /// Decodes polygons from the geometry buffer.
+    pub fn decode_polygons(&mut self) -> Result<Vec<Vec<Vec<[i32; 2]>>>> {
+        let mut rings: Vec<Vec<[i32; 2]>> = Vec::new();
+        // the ring being decoded, and the command expected next
+        let mut ring: Vec<[i32; 2]> = Vec::new();
+        let mut expected = GEOM_COMMAND_MOVE_TO;
+        let mut failed = false;
+
+        while let Some(command) = self.next_command() {
+            let (command, raw) = match command {
+                Ok(command) => command,
+                Err(err) => {
+                    self.recover(err)?;
+                    failed = true;
+                    break;
+                }
+            };
+            match command {
+                Command::MoveTo(coord) => {
+                    if raw.count != 1 {
+                        self.recover(invalid_count(raw))?;
+                    } else if expected != GEOM_COMMAND_MOVE_TO {
+                        // missing ClosePath, the ring is closed implicitly
+                        self.recover(unexpected(expected, raw))?;
+                    }
+                    let ring = std::mem::replace(&mut ring, vec![coord]);
+                    push_ring(&mut rings, ring);
+                    expected = GEOM_COMMAND_LINE_TO;
+                }
+                Command::LineTo(coord) if !ring.is_empty() => {
+                    ring.push(coord);
+                    expected = GEOM_COMMAND_CLOSE_PATH;
+                }
+                Command::LineTo(_) => self.recover(unexpected(GEOM_COMMAND_MOVE_TO, raw))?,
+                Command::Empty => self.recover(invalid_count(raw))?,
+                Command::ClosePath => {
+                    if expected != GEOM_COMMAND_CLOSE_PATH {
+                        self.recover(unexpected(expected, raw))?;
+                    }
+                    let ring = std::mem::take(&mut ring);
+                    push_ring(&mut rings, ring);
+                    expected = GEOM_COMMAND_MOVE_TO;
+                }
             }
+        }
+        if expected != GEOM_COMMAND_MOVE_TO && !failed {
+            self.recover(Error::Truncated {
+                offset: self.commands.offset(),
+            })?;
+        }
+        push_ring(&mut rings, ring);
 
-            let cmd_int = self.buf[self.pos];
-            self.pos += 1;
-
-            let cmd = cmd_int & 0x7;
+        Ok(match self.ring_classification {
+            RingClassification::Winding => group_rings_by_winding(rings),
+            RingClassification::Containment => group_rings_by_containment(rings),
+        })
+    }
+}
 
-            if cmd != GEOM_COMMAND_CLOSE_PATH {
-                return Err(format!("Expected ClosePath command, got {}", cmd));
-            }
+/// Adds a decoded line string, dropping it if it has fewer than two points.
+fn push_linestring(linestrings: &mut Vec<Vec<[i32; 2]>>, line: Vec<[i32; 2]>) {
+    if line.len() >= 2 {
+        linestrings.push(line);
+    }
+}
 
-            // Calculate signed area to determine if this is an exterior or interior ring
-            // MVT spec: exterior rings are clockwise (positive area), interior rings are counter-clockwise (negative area)
-            let signed_area = calculate_signed_area(&ring);
-            let is_exterior = signed_area > 0.0;
+/// Adds a decoded ring, dropping it if it has fewer than two points.
+fn push_ring(rings: &mut Vec<Vec<[i32; 2]>>, ring: Vec<[i32; 2]>) {
+    if ring.len() >= 2 {
+        rings.push(ring);
+    }
+}
 
-            // If this is an exterior ring and we already have rings, finalize the current polygon
-            if is_exterior && !current_polygon.is_empty() {
-                polygons.push(current_polygon);
-                current_polygon = Vec::new();
+/// Groups rings into polygons by their winding order (MVT 2.x, spec 4.3.4.4).
+fn group_rings_by_winding(rings: Vec<Vec<[i32; 2]>>) -> Vec<Vec<Vec<[i32; 2]>>> {
+    let mut polygons: Vec<Vec<Vec<[i32; 2]>>> = Vec::new();
+    for mut ring in rings {
+        // MVT spec: exterior rings are clockwise (positive area), interior rings are counter-clockwise (negative area)
+        let signed_area = calculate_signed_area(&ring);
+        if signed_area == 0.0 {
+            // neither an exterior nor an interior ring
+            continue;
+        }
+        match polygons.last_mut() {
+            Some(polygon) if signed_area < 0.0 => polygon.push(ring),
+            _ => {
+                // an interior ring without a preceding exterior ring becomes an exterior ring
+                if signed_area < 0.0 {
+                    ring.reverse();
+                }
+                polygons.push(vec![ring]);
             }
-
-            current_polygon.push(ring);
         }
+    }
+    polygons
+}
 
-        // Finalize any remaining polygon
-        if !current_polygon.is_empty() {
-            polygons.push(current_polygon);
+/// Groups rings into polygons by containment, ignoring their winding order (MVT 1.x).
+///
+/// Each ring becomes a hole of the smallest exterior ring containing it; rings inside holes
+/// become exterior rings again. Polygons are ordered by the first appearance of their exterior
+/// ring, and rings are reoriented to follow the MVT 2.x winding order.
+fn group_rings_by_containment(rings: Vec<Vec<[i32; 2]>>) -> Vec<Vec<Vec<[i32; 2]>>> {
+    let mut rings: Vec<(Vec<[i32; 2]>, f64)> = rings
+        .into_iter()
+        .map(|ring| {
+            let area = calculate_signed_area(&ring);
+            (ring, area)
+        })
+        .filter(|(_, area)| *area != 0.0)
+        .collect();
+
+    // larger rings first, so that containers are classified before the rings they contain
+    let mut order: Vec<usize> = (0..rings.len()).collect();
+    order.sort_by(|&a, &b| rings[b].1.abs().total_cmp(&rings[a].1.abs()));
+
+    // the exterior ring each ring belongs to (itself for exterior rings)
+    let mut shell_of: Vec<usize> = (0..rings.len()).collect();
+    for (k, &i) in order.iter().enumerate() {
+        let container = order[..k]
+            .iter()
+            .rev()
+            .find(|&&j| ring_contains(&rings[j].0, &rings[i].0));
+        if let Some(&j) = container
+            && shell_of[j] == j
+        {
+            shell_of[i] = j;
         }
+    }
 
-        Ok(polygons)
+    let mut polygon_of = vec![usize::MAX; rings.len()];
+    let mut polygons: Vec<Vec<Vec<[i32; 2]>>> = Vec::new();
+    for i in 0..rings.len() {
+        if shell_of[i] == i {
+            polygon_of[i] = polygons.len();
+            polygons.push(Vec::new());
+        }
     }
+    for (i, (mut ring, area)) in rings.drain(..).enumerate() {
+        let exterior = shell_of[i] == i;
+        if (area > 0.0) != exterior {
+            ring.reverse();
+        }
+        polygons[polygon_of[shell_of[i]]].push(ring);
+    }
+    polygons
+}
 
-    /// Reads a coordinate pair from the buffer.
-    fn read_coord(&mut self) -> Result<[i32; 2], String> {
-        if self.pos + 1 >= self.buf.len() {
-            return Err("Unexpected end of buffer while reading coordinates".to_string());
+/// Returns whether `inner` lies inside `outer`, judged by the first vertex of `inner`
+/// that is not on the boundary of `outer`.
+fn ring_contains(outer: &[[i32; 2]], inner: &[[i32; 2]]) -> bool {
+    inner
+        .iter()
+        .find_map(|&point| point_in_ring(point, outer))
+        .unwrap_or(false)
+}
+
+/// Tests whether a point is inside a ring with the even-odd rule.
+///
+/// Returns `None` if the point lies on the boundary of the ring.
+fn point_in_ring(point: [i32; 2], ring: &[[i32; 2]]) -> Option<bool> {
+    let [px, py] = point.map(i64::from);
+    let mut inside = false;
+    for (i, &a) in ring.iter().enumerate() {
+        let b = ring[(i + 1) % ring.len()];
+        let [ax, ay] = a.map(i64::from);
+        let [bx, by] = b.map(i64::from);
+
+        let cross = (bx - ax) * (py - ay) - (by - ay) * (px - ax);
+        if cross == 0
+            && px >= ax.min(bx)
+            && px <= ax.max(bx)
+            && py >= ay.min(by)
+            && py <= ay.max(by)
+        {
+            return None;
+        }
+        // does the edge cross the ray from the point towards +x?
+        if (ay > py) != (by > py) && (cross > 0) == (by > ay) {
+            inside = !inside;
         }
+    }
+    Some(inside)
+}
 
-        let dx = unzigzag(self.buf[self.pos]);
-        let dy = unzigzag(self.buf[self.pos + 1]);
-        self.pos += 2;
+#[inline]
+fn unexpected(expected: u32, raw: RawCommand) -> Error {
+    Error::UnexpectedCommand {
+        expected,
+        found: raw.id,
+        offset: raw.offset,
+    }
+}
 
-        self.cursor_x += dx;
-        self.cursor_y += dy;
+/// Line strings and rings must start with a MoveTo command of count 1, and no command may
+/// have a count of zero.
+#[inline]
+fn invalid_count(raw: RawCommand) -> Error {
+    Error::InvalidCommandCount {
+        command: raw.id,
+        count: raw.count,
+        offset: raw.offset,
+    }
+}
 
-        Ok([self.cursor_x, self.cursor_y])
+/// Decodes a geometry, maps every coordinate and re-encodes it with `encoder`.
+///
+/// If `pre_clipper` is given, the geometry is clipped by it before mapping, which keeps
+/// mapped coordinates within range when `map` scales them up. The encoder may clip again
+/// after mapping. Line strings that collapse to a single point and rings that collapse to
+/// zero area are dropped. Geometries of unknown type are dropped (an empty buffer is returned).
+pub(crate) fn transform_geometry(
+    geom_type: GeomType,
+    geometry: &[u32],
+    ring_classification: RingClassification,
+    pre_clipper: Option<Clipper>,
+    map: impl Fn([i32; 2]) -> [i32; 2],
+    mut encoder: GeometryEncoder,
+) -> Result<Vec<u32>> {
+    let mut decoder = GeometryDecoder::new(geometry);
+    decoder.set_ring_classification(ring_classification);
+    match geom_type {
+        GeomType::Point => {
+            let mut points = decoder.decode_points()?;
+            if let Some(clipper) = pre_clipper {
+                points = clipper.clip_points(points);
+            }
+            encoder.add_points(points.into_iter().map(map));
+        }
+        GeomType::Linestring => {
+            for line in decoder.decode_linestrings()? {
+                let parts = match pre_clipper {
+                    Some(clipper) => clipper.clip_linestring(line),
+                    None => vec![line],
+                };
+                for part in parts {
+                    let mut part: Vec<[i32; 2]> = part.into_iter().map(&map).collect();
+                    part.dedup();
+                    // drop lines that collapse to a single point
+                    if part.len() >= 2 {
+                        encoder.add_linestring(part);
+                    }
+                }
+            }
+        }
+        GeomType::Polygon => {
+            for polygon in decoder.decode_polygons()? {
+                let mut rings = polygon.into_iter();
+                let Some(exterior) = rings.next() else {
+                    continue;
+                };
+                let rings: Vec<Vec<[i32; 2]>> = match pre_clipper {
+                    Some(clipper) => {
+                        let Some(exterior) = clipper.clip_ring(exterior) else {
+                            continue;
+                        };
+                        std::iter::once(exterior)
+                            .chain(rings.filter_map(|ring| clipper.clip_ring(ring)))
+                            .collect()
+                    }
+                    None => std::iter::once(exterior).chain(rings).collect(),
+                };
+                encoder.add_polygon(
+                    rings
+                        .into_iter()
+                        .map(|ring| ring.into_iter().map(&map).collect::<Vec<_>>()),
+                );
+            }
+        }
+        GeomType::Unknown => {}
     }
+    Ok(encoder.into_vec())
 }
 
 /// Calculates the signed area of a ring using the shoelace formula
 /// Positive area means clockwise (exterior ring), negative means counter-clockwise (interior ring)
-fn calculate_signed_area(ring: &[[i32; 2]]) -> f64 {
+pub(crate) fn calculate_signed_area(ring: &[[i32; 2]]) -> f64 {
     if ring.len() < 3 {
         return 0.0;
     }
@@ -355,6 +837,20 @@ fn calculate_signed_area(ring: &[[i32; 2]]) -> f64 {
     area as f64 / 2.0
 }
 
+/// Removes consecutive duplicate vertices (including the closing vertex) from a ring.
+///
+/// Returns `None` if the ring has fewer than three distinct vertices or zero area.
+pub(crate) fn normalize_ring(mut ring: Vec<[i32; 2]>) -> Option<Vec<[i32; 2]>> {
+    ring.dedup();
+    while ring.len() > 1 && ring.first() == ring.last() {
+        ring.pop();
+    }
+    if ring.len() < 3 || calculate_signed_area(&ring) == 0.0 {
+        return None;
+    }
+    Some(ring)
+}
+
 /// zig-zag encoding
 ///
 /// See: https://protobuf.dev/programming-guides/encoding/#signed-ints
@@ -417,6 +913,371 @@ mod tests {
         assert_eq!(geometry[5], 0); // dy = 0 (repeated point)
     }
 
+    #[test]
+    fn test_encoder_with_clipper() {
+        let mut encoder = GeometryEncoder::with_clipper(Clipper::new(100, 0));
+        encoder.add_points([[10, 10], [200, 10]]);
+        let geometry = encoder.into_vec();
+        let decoded = GeometryDecoder::new(&geometry).decode_points().unwrap();
+        assert_eq!(decoded, [[10, 10]]);
+
+        let mut encoder = GeometryEncoder::with_clipper(Clipper::new(100, 0));
+        encoder.add_linestring([[50, 20], [200, 20], [200, 80], [50, 80]]);
+        let geometry = encoder.into_vec();
+        let decoded = GeometryDecoder::new(&geometry)
+            .decode_linestrings()
+            .unwrap();
+        assert_eq!(
+            decoded,
+            [vec![[50, 20], [100, 20]], vec![[100, 80], [50, 80]]]
+        );
+
+        let mut encoder = GeometryEncoder::with_clipper(Clipper::new(100, 0));
+        encoder.add_ring([[200, 200], [300, 200], [300, 300]]); // dropped
+        encoder.add_ring([[50, 50], [150, 50], [150, 150], [50, 150]]);
+        let geometry = encoder.into_vec();
+        let decoded = GeometryDecoder::new(&geometry).decode_polygons().unwrap();
+        assert_eq!(
+            decoded,
+            [vec![vec![[50, 100], [50, 50], [100, 50], [100, 100]]]]
+        );
+    }
+
+    #[test]
+    fn test_add_polygon_fixes_winding() {
+        // exterior ring is counter-clockwise and closed, hole is clockwise
+        let exterior = [[0, 0], [0, 100], [100, 100], [100, 0], [0, 0]];
+        let hole = [[10, 10], [20, 10], [20, 20], [10, 20]];
+        let mut encoder = GeometryEncoder::new();
+        encoder.add_polygon([exterior.to_vec(), hole.to_vec()]);
+        encoder.add_polygon([[[200, 200], [200, 300], [300, 300], [300, 200]]]);
+        let geometry = encoder.into_vec();
+
+        let decoded = GeometryDecoder::new(&geometry).decode_polygons().unwrap();
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0].len(), 2);
+        assert!(calculate_signed_area(&decoded[0][0]) > 0.0);
+        assert!(calculate_signed_area(&decoded[0][1]) < 0.0);
+        assert_eq!(decoded[0][0].len(), 4);
+        assert_eq!(decoded[1].len(), 1);
+        assert!(calculate_signed_area(&decoded[1][0]) > 0.0);
+    }
+
+    #[test]
+    fn test_add_polygon_skips_degenerate_rings() {
+        let mut encoder = GeometryEncoder::new();
+        encoder.add_polygon([vec![[0, 0], [10, 10], [20, 20]]]); // zero area
+        encoder.add_polygon(Vec::<Vec<[i32; 2]>>::new());
+        assert!(encoder.into_vec().is_empty());
+
+        let mut encoder = GeometryEncoder::with_clipper(Clipper::new(100, 0));
+        encoder.add_polygon([
+            vec![[0, 0], [100, 0], [100, 100], [0, 100]],
+            vec![[200, 200], [210, 200], [210, 210]], // hole outside the tile
+        ]);
+        let geometry = encoder.into_vec();
+        let decoded = GeometryDecoder::new(&geometry).decode_polygons().unwrap();
+        assert_eq!(
+            decoded,
+            [vec![vec![[0, 0], [100, 0], [100, 100], [0, 100]]]]
+        );
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        // LineTo in point geometry
+        let mut decoder = GeometryDecoder::new(&[9, 0, 0, 10, 2, 2]);
+        assert_eq!(
+            decoder.decode_points(),
+            Err(Error::UnexpectedCommand {
+                expected: GEOM_COMMAND_MOVE_TO,
+                found: GEOM_COMMAND_LINE_TO,
+                offset: 3,
+            })
+        );
+
+        // MoveTo with count 2 in linestring
+        let mut decoder = GeometryDecoder::new(&[17, 0, 0, 2, 2]);
+        assert_eq!(
+            decoder.decode_linestrings(),
+            Err(Error::InvalidCommandCount {
+                command: GEOM_COMMAND_MOVE_TO,
+                count: 2,
+                offset: 0,
+            })
+        );
+
+        // truncated coordinates
+        let mut decoder = GeometryDecoder::new(&[9, 0, 0, 18, 2, 2, 2]);
+        assert_eq!(
+            decoder.decode_linestrings(),
+            Err(Error::Truncated { offset: 6 })
+        );
+
+        // missing ClosePath
+        let mut decoder = GeometryDecoder::new(&[9, 0, 0, 26, 2, 0, 0, 2, 1, 0]);
+        assert_eq!(
+            decoder.decode_polygons(),
+            Err(Error::Truncated { offset: 10 })
+        );
+    }
+
+    #[test]
+    fn test_commands() {
+        let mut encoder = GeometryEncoder::new();
+        encoder.add_ring([[0, 0], [10, 0], [10, 10]]);
+        encoder.add_linestring([[5, 5], [6, 6]]);
+        let geometry = encoder.into_vec();
+
+        let commands: Vec<_> = GeometryDecoder::new(&geometry)
+            .commands()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(
+            commands,
+            [
+                Command::MoveTo([0, 0]),
+                Command::LineTo([10, 0]),
+                Command::LineTo([10, 10]),
+                Command::ClosePath,
+                Command::MoveTo([5, 5]),
+                Command::LineTo([6, 6]),
+            ]
+        );
+
+        let mut commands = Commands::new(&geometry);
+        assert_eq!(commands.raw_command(), None);
+        commands.nth(2).unwrap().unwrap();
+        let raw = commands.raw_command().unwrap();
+        assert_eq!(
+            raw,
+            RawCommand {
+                id: GEOM_COMMAND_LINE_TO,
+                count: 2,
+                offset: 3,
+            }
+        );
+        assert_eq!(raw.integer(), geometry[3]);
+    }
+
+    #[test]
+    fn test_commands_errors() {
+        let mut commands = Commands::new(&[9, 0, 0, 3, 0, 0]);
+        assert_eq!(commands.next(), Some(Ok(Command::MoveTo([0, 0]))));
+        assert_eq!(
+            commands.next(),
+            Some(Err(Error::UnknownCommand {
+                command: 3,
+                offset: 3
+            }))
+        );
+        assert_eq!(commands.next(), None);
+
+        let mut commands = Commands::new(&[17, 2, 2, 2]);
+        assert_eq!(commands.next(), Some(Ok(Command::MoveTo([1, 1]))));
+        assert_eq!(commands.next(), Some(Err(Error::Truncated { offset: 3 })));
+        assert_eq!(commands.next(), None);
+
+        // commands with a count of zero are skipped unless included
+        let geometry = [9, 0, 0, 2, 10, 2, 2];
+        let events: Vec<_> = Commands::new(&geometry).map(Result::unwrap).collect();
+        assert_eq!(events, [Command::MoveTo([0, 0]), Command::LineTo([1, 1])]);
+        let mut commands = Commands::new(&geometry);
+        commands.set_include_empty(true);
+        assert_eq!(commands.nth(1), Some(Ok(Command::Empty)));
+        assert_eq!(
+            commands.raw_command(),
+            Some(RawCommand {
+                id: GEOM_COMMAND_LINE_TO,
+                count: 0,
+                offset: 3
+            })
+        );
+        assert_eq!(commands.next(), Some(Ok(Command::LineTo([1, 1]))));
+
+        // MoveTo must be followed by LineTo in line strings
+        let mut decoder = GeometryDecoder::new(&[9, 0, 0, 9, 2, 2]);
+        assert_eq!(
+            decoder.decode_linestrings(),
+            Err(Error::UnexpectedCommand {
+                expected: GEOM_COMMAND_LINE_TO,
+                found: GEOM_COMMAND_MOVE_TO,
+                offset: 3,
+            })
+        );
+    }
+
+    #[test]
+    fn test_lenient_linestrings() {
+        let lenient = |geometry: &[u32]| {
+            let mut decoder = GeometryDecoder::with_policy(geometry, DecodePolicy::Lenient);
+            let lines = decoder.decode_linestrings().unwrap();
+            (lines, decoder.take_warnings())
+        };
+
+        // MoveTo with count 2: each point starts a new part, the single-point part is dropped
+        let geometry = [17, 0, 0, 20, 20, 10, 2, 2];
+        assert!(
+            GeometryDecoder::new(&geometry)
+                .decode_linestrings()
+                .is_err()
+        );
+        assert_eq!(
+            lenient(&geometry),
+            (
+                vec![vec![[10, 10], [11, 11]]],
+                vec![Error::InvalidCommandCount {
+                    command: GEOM_COMMAND_MOVE_TO,
+                    count: 2,
+                    offset: 0,
+                }]
+            )
+        );
+
+        // LineTo with zero count
+        assert_eq!(
+            lenient(&[9, 0, 0, 2, 9, 4, 4, 10, 2, 2]),
+            (
+                vec![vec![[2, 2], [3, 3]]],
+                vec![Error::InvalidCommandCount {
+                    command: GEOM_COMMAND_LINE_TO,
+                    count: 0,
+                    offset: 3,
+                }]
+            )
+        );
+
+        // ClosePath closes the line string
+        assert_eq!(
+            lenient(&[9, 0, 0, 18, 20, 0, 0, 20, 15]),
+            (
+                vec![vec![[0, 0], [10, 0], [10, 10], [0, 0]]],
+                vec![Error::UnexpectedCommand {
+                    expected: GEOM_COMMAND_MOVE_TO,
+                    found: GEOM_COMMAND_CLOSE_PATH,
+                    offset: 8,
+                }]
+            )
+        );
+    }
+
+    #[test]
+    fn test_lenient_polygons_and_points() {
+        let mut encoder = GeometryEncoder::new();
+        encoder.add_ring([[0, 0], [10, 0], [10, 10], [0, 10]]);
+        encoder.add_ring([[20, 20], [30, 20], [30, 30], [20, 30]]);
+        let mut geometry = encoder.into_vec();
+        assert_eq!(geometry.remove(10), GEOM_COMMAND_CLOSE_PATH_WITH_COUNT1);
+
+        // missing ClosePath
+        let missing_close_path = || Error::UnexpectedCommand {
+            expected: GEOM_COMMAND_CLOSE_PATH,
+            found: GEOM_COMMAND_MOVE_TO,
+            offset: 10,
+        };
+        assert_eq!(
+            GeometryDecoder::new(&geometry).decode_polygons(),
+            Err(missing_close_path())
+        );
+        let mut decoder = GeometryDecoder::with_policy(&geometry, DecodePolicy::Lenient);
+        let polygons = decoder.decode_polygons().unwrap();
+        assert_eq!(polygons.len(), 2);
+        assert_eq!(polygons[0], [vec![[0, 0], [10, 0], [10, 10], [0, 10]]]);
+        assert_eq!(decoder.warnings(), [missing_close_path()]);
+
+        // LineTo with zero count inside a ring
+        let zero_line_to = [9, 0, 0, 2, 18, 20, 0, 0, 20, 15];
+        let invalid_count = || Error::InvalidCommandCount {
+            command: GEOM_COMMAND_LINE_TO,
+            count: 0,
+            offset: 3,
+        };
+        assert_eq!(
+            GeometryDecoder::new(&zero_line_to).decode_polygons(),
+            Err(invalid_count())
+        );
+        let mut decoder = GeometryDecoder::with_policy(&zero_line_to, DecodePolicy::Lenient);
+        assert_eq!(
+            decoder.decode_polygons().unwrap(),
+            [vec![vec![[0, 0], [10, 0], [10, 10]]]]
+        );
+        assert_eq!(decoder.warnings(), [invalid_count()]);
+
+        // truncated buffer keeps what was decoded so far
+        geometry.truncate(19);
+        let mut decoder = GeometryDecoder::with_policy(&geometry, DecodePolicy::Lenient);
+        let polygons = decoder.decode_polygons().unwrap();
+        assert_eq!(polygons.len(), 2);
+        assert_eq!(polygons[1], [vec![[20, 20], [30, 20], [30, 30]]]);
+        assert_eq!(decoder.warnings()[1], Error::Truncated { offset: 18 });
+
+        // LineTo in point geometry is skipped
+        let mut decoder =
+            GeometryDecoder::with_policy(&[9, 0, 0, 10, 2, 2, 9, 4, 4], DecodePolicy::Lenient);
+        assert_eq!(decoder.decode_points().unwrap(), [[0, 0], [3, 3]]);
+        assert_eq!(decoder.warnings().len(), 1);
+    }
+
+    #[test]
+    fn test_ring_classification() {
+        let shell = [[0, 0], [100, 0], [100, 100], [0, 100]];
+        let island = [[40, 40], [60, 40], [60, 60], [40, 60]];
+        let hole = [[20, 20], [20, 80], [80, 80], [80, 20]];
+        let other = [[200, 0], [300, 0], [300, 100], [200, 100]];
+        let reversed = |ring: &[[i32; 2]; 4]| ring.iter().rev().copied().collect::<Vec<_>>();
+
+        // v1: winding order is arbitrary and holes may come first
+        let mut encoder = GeometryEncoder::new();
+        encoder.add_ring(island);
+        encoder.add_ring(reversed(&other));
+        encoder.add_ring(shell);
+        encoder.add_ring(hole.iter().rev().copied());
+        let geometry = encoder.into_vec();
+
+        let mut decoder = GeometryDecoder::new(&geometry);
+        decoder.set_ring_classification(RingClassification::for_version(1));
+        let polygons = decoder.decode_polygons().unwrap();
+        assert_eq!(
+            polygons,
+            [
+                vec![island.to_vec()],
+                vec![other.to_vec()],
+                vec![shell.to_vec(), hole.to_vec()],
+            ]
+        );
+
+        // v2: zero-area rings are dropped, a leading interior ring becomes an exterior ring
+        let mut encoder = GeometryEncoder::new();
+        encoder.add_ring(hole);
+        encoder.add_ring(shell);
+        encoder.add_ring([[10, 10], [20, 20], [30, 30]]);
+        encoder.add_ring(reversed(&island));
+        let geometry = encoder.into_vec();
+
+        let mut decoder = GeometryDecoder::new(&geometry);
+        decoder.set_ring_classification(RingClassification::for_version(2));
+        let polygons = decoder.decode_polygons().unwrap();
+        assert_eq!(
+            polygons,
+            [
+                vec![reversed(&hole)],
+                vec![shell.to_vec(), reversed(&island)],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_point_in_ring() {
+        let ring = [[0, 0], [10, 0], [10, 10], [0, 10]];
+        assert_eq!(point_in_ring([5, 5], &ring), Some(true));
+        assert_eq!(point_in_ring([15, 5], &ring), Some(false));
+        assert_eq!(point_in_ring([-1, 0], &ring), Some(false));
+        assert_eq!(point_in_ring([10, 5], &ring), None);
+        assert_eq!(point_in_ring([0, 0], &ring), None);
+        // touching the container at a vertex does not decide containment
+        assert!(ring_contains(&ring, &[[0, 0], [5, 2], [2, 5]]));
+    }
+
     #[test]
     fn test_unzigzag() {
         assert_eq!(unzigzag(0), 0);
@@ -1,8 +1,30 @@
+pub mod clip;
+pub mod cover;
+pub mod error;
+pub mod feature;
+#[cfg(feature = "geo-types")]
+pub mod geo;
+#[cfg(feature = "geojson")]
+pub mod geojson;
 pub mod geometry;
+#[cfg(feature = "mbtiles")]
+pub mod mbtiles;
+pub mod merge;
+pub mod overzoom;
+#[cfg(feature = "pmtiles")]
+pub mod pmtiles;
+pub mod reader;
+pub mod rescale;
+pub mod simplify;
 pub mod tag;
 pub mod tileid;
+pub mod tilemath;
+pub mod validate;
 pub mod vector_tile;
 pub mod webmercator;
+pub mod writer;
+
+pub use error::{Error, Result};
 
 /// Tile coordinate in (z, x, y) format.
 pub type TileZXY = (u8, u32, u32);
@@ -0,0 +1,381 @@
+//! Reading and writing MBTiles (SQLite) files.
+//!
+//! See the [MBTiles specification](https://github.com/mapbox/mbtiles-spec/blob/master/1.3/spec.md).
+//! Tiles are addressed with XYZ coordinates; the TMS row flip used in the database is applied internally.
+
+use std::path::Path;
+
+use rusqlite::{Connection, OpenFlags, OptionalExtension, params};
+use serde_json::Value as JsonValue;
+
+use crate::TileZXY;
+use crate::error::{Error, Result};
+use crate::tilemath::flip_y;
+
+/// Table layout of the tiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Schema {
+    /// A single `tiles` table.
+    Flat,
+    /// `map` and `images` tables joined by a `tiles` view, storing identical tiles only once.
+    Deduplicated,
+}
+
+/// An MBTiles file.
+pub struct MbTiles {
+    conn: Connection,
+    schema: Schema,
+}
+
+impl MbTiles {
+    /// Opens an existing MBTiles file read-only, detecting its schema.
+    ///
+    /// The file is never created or modified; use [`MbTiles::create`] to write tiles.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY
+                | OpenFlags::SQLITE_OPEN_URI
+                | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        let has_table = |name: &str| -> Result<bool> {
+            Ok(conn
+                .query_row(
+                    "SELECT 1 FROM sqlite_master WHERE name = ?1 AND type IN ('table', 'view')",
+                    [name],
+                    |_| Ok(()),
+                )
+                .optional()?
+                .is_some())
+        };
+        if !has_table("tiles")? || !has_table("metadata")? {
+            return Err(invalid("missing tiles or metadata table"));
+        }
+        let schema = if has_table("map")? && has_table("images")? {
+            Schema::Deduplicated
+        } else {
+            Schema::Flat
+        };
+        Ok(Self { conn, schema })
+    }
+
+    /// Creates a new MBTiles file with the given schema. The file must not already contain tables.
+    pub fn create(path: impl AsRef<Path>, schema: Schema) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch("CREATE TABLE metadata (name TEXT, value TEXT, PRIMARY KEY (name));")?;
+        match schema {
+            Schema::Flat => conn.execute_batch(
+                "CREATE TABLE tiles (zoom_level INTEGER, tile_column INTEGER, tile_row INTEGER, tile_data BLOB,
+                     PRIMARY KEY (zoom_level, tile_column, tile_row));",
+            )?,
+            Schema::Deduplicated => conn.execute_batch(
+                "CREATE TABLE map (zoom_level INTEGER, tile_column INTEGER, tile_row INTEGER, tile_id TEXT,
+                     PRIMARY KEY (zoom_level, tile_column, tile_row));
+                 CREATE TABLE images (tile_id TEXT, tile_data BLOB, PRIMARY KEY (tile_id));
+                 CREATE VIEW tiles AS
+                     SELECT map.zoom_level AS zoom_level, map.tile_column AS tile_column,
+                         map.tile_row AS tile_row, images.tile_data AS tile_data
+                     FROM map JOIN images ON images.tile_id = map.tile_id;",
+            )?,
+        }
+        Ok(Self { conn, schema })
+    }
+
+    /// Returns the schema of the file.
+    #[inline]
+    pub fn schema(&self) -> Schema {
+        self.schema
+    }
+
+    /// Reads a tile. Returns `None` if the file doesn't contain it.
+    pub fn get_tile(&self, (z, x, y): TileZXY) -> Result<Option<Vec<u8>>> {
+        if z > 31 || y >= 1 << z {
+            return Ok(None);
+        }
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT tile_data FROM tiles WHERE zoom_level = ?1 AND tile_column = ?2 AND tile_row = ?3",
+                params![z, x, flip_y((z, x, y)).2],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Writes a tile, replacing any existing tile with the same coordinate.
+    pub fn put_tile(&mut self, tile: TileZXY, data: &[u8]) -> Result<()> {
+        self.put_tiles([(tile, data)])
+    }
+
+    /// Writes tiles in a single transaction, replacing existing tiles with the same coordinates.
+    pub fn put_tiles<D: AsRef<[u8]>>(
+        &mut self,
+        tiles: impl IntoIterator<Item = (TileZXY, D)>,
+    ) -> Result<()> {
+        let schema = self.schema;
+        let tx = self.conn.transaction()?;
+        for ((z, x, y), data) in tiles {
+            if z > 31 || x >= 1 << z || y >= 1 << z {
+                return Err(invalid("tile coordinate out of range"));
+            }
+            let data = data.as_ref();
+            let (_, _, row) = flip_y((z, x, y));
+            match schema {
+                Schema::Flat => {
+                    tx.prepare_cached(
+                        "INSERT OR REPLACE INTO tiles (zoom_level, tile_column, tile_row, tile_data)
+                         VALUES (?1, ?2, ?3, ?4)",
+                    )?
+                    .execute(params![z, x, row, data])?;
+                }
+                Schema::Deduplicated => {
+                    let tile_id = insert_image(&tx, data)?;
+                    tx.prepare_cached(
+                        "INSERT OR REPLACE INTO map (zoom_level, tile_column, tile_row, tile_id)
+                         VALUES (?1, ?2, ?3, ?4)",
+                    )?
+                    .execute(params![z, x, row, tile_id])?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Returns the coordinates of all tiles in the file.
+    pub fn tile_coords(&self) -> Result<Vec<TileZXY>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT zoom_level, tile_column, tile_row FROM tiles")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, u8>(0)?,
+                row.get::<_, u32>(1)?,
+                row.get::<_, u32>(2)?,
+            ))
+        })?;
+        let mut coords = Vec::new();
+        for row in rows {
+            let (z, x, row) = row?;
+            if z > 31 || row >= 1 << z {
+                return Err(invalid("tile row out of range"));
+            }
+            coords.push(flip_y((z, x, row)));
+        }
+        Ok(coords)
+    }
+
+    /// Reads a metadata value.
+    pub fn metadata(&self, name: &str) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT value FROM metadata WHERE name = ?1",
+                [name],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Reads all metadata as (name, value) pairs.
+    pub fn all_metadata(&self) -> Result<Vec<(String, String)>> {
+        let mut stmt = self.conn.prepare("SELECT name, value FROM metadata")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Writes a metadata value, replacing any existing value.
+    pub fn set_metadata(&mut self, name: &str, value: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO metadata (name, value) VALUES (?1, ?2)",
+            [name, value],
+        )?;
+        Ok(())
+    }
+
+    /// Reads the `json` metadata value, which holds `vector_layers` for vector tilesets.
+    pub fn json_metadata(&self) -> Result<Option<JsonValue>> {
+        match self.metadata("json")? {
+            Some(json) => serde_json::from_str(&json)
+                .map(Some)
+                .map_err(|_| invalid("json metadata is not valid JSON")),
+            None => Ok(None),
+        }
+    }
+
+    /// Writes the `json` metadata value.
+    pub fn set_json_metadata(&mut self, json: &JsonValue) -> Result<()> {
+        self.set_metadata("json", &json.to_string())
+    }
+
+    /// Returns the `vector_layers` array in the `json` metadata, if any.
+    pub fn vector_layers(&self) -> Result<Option<Vec<JsonValue>>> {
+        let Some(mut json) = self.json_metadata()? else {
+            return Ok(None);
+        };
+        match json.get_mut("vector_layers").map(JsonValue::take) {
+            Some(JsonValue::Array(layers)) => Ok(Some(layers)),
+            Some(_) => Err(invalid("vector_layers must be an array")),
+            None => Ok(None),
+        }
+    }
+
+    /// Consumes the file and returns the underlying connection.
+    #[inline]
+    pub fn into_inner(self) -> Connection {
+        self.conn
+    }
+}
+
+/// Inserts tile data into the `images` table unless an identical image exists, and returns its ID.
+///
+/// The ID is the MD5 hex digest of the data, as used by other MBTiles tools.
+fn insert_image(conn: &Connection, data: &[u8]) -> Result<String> {
+    let tile_id = format!("{:x}", md5::compute(data));
+    conn.prepare_cached("INSERT OR IGNORE INTO images (tile_id, tile_data) VALUES (?1, ?2)")?
+        .execute(params![tile_id, data])?;
+    Ok(tile_id)
+}
+
+#[inline]
+fn invalid(reason: &str) -> Error {
+    Error::InvalidMbTiles {
+        reason: reason.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// Temporary file that is removed when dropped.
+    struct TempPath(std::path::PathBuf);
+
+    impl TempPath {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "tinymvt-{}-{}.mbtiles",
+                std::process::id(),
+                name
+            ));
+            let _ = std::fs::remove_file(&path);
+            Self(path)
+        }
+    }
+
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for schema in [Schema::Flat, Schema::Deduplicated] {
+            let path = TempPath::new(&format!("{:?}", schema));
+            {
+                let mut mbtiles = MbTiles::create(&path.0, schema).unwrap();
+                mbtiles.set_metadata("name", "test").unwrap();
+                mbtiles.set_metadata("format", "pbf").unwrap();
+                mbtiles
+                    .set_json_metadata(&json!({"vector_layers": [{"id": "roads", "fields": {}}]}))
+                    .unwrap();
+                mbtiles
+                    .put_tiles([
+                        ((0, 0, 0), b"ocean"),
+                        ((1, 0, 0), b"ocean"),
+                        ((1, 1, 0), b"land!"),
+                    ])
+                    .unwrap();
+                mbtiles.put_tile((1, 1, 0), b"land").unwrap();
+            }
+
+            let mbtiles = MbTiles::open(&path.0).unwrap();
+            assert_eq!(mbtiles.schema(), schema);
+            assert_eq!(
+                mbtiles.get_tile((0, 0, 0)).unwrap().as_deref(),
+                Some(&b"ocean"[..])
+            );
+            assert_eq!(
+                mbtiles.get_tile((1, 1, 0)).unwrap().as_deref(),
+                Some(&b"land"[..])
+            );
+            assert_eq!(mbtiles.get_tile((1, 1, 1)).unwrap(), None);
+            assert_eq!(mbtiles.get_tile((1, 0, 2)).unwrap(), None);
+
+            let mut coords = mbtiles.tile_coords().unwrap();
+            coords.sort();
+            assert_eq!(coords, [(0, 0, 0), (1, 0, 0), (1, 1, 0)]);
+
+            // XYZ y=0 is stored as TMS row 1 at zoom 1
+            let row: u32 = mbtiles
+                .into_inner()
+                .query_row(
+                    "SELECT tile_row FROM tiles WHERE zoom_level = 1 AND tile_column = 1",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(row, 1);
+
+            let mut mbtiles = MbTiles::open(&path.0).unwrap();
+            assert_eq!(mbtiles.metadata("name").unwrap().as_deref(), Some("test"));
+            assert_eq!(mbtiles.metadata("missing").unwrap(), None);
+            assert_eq!(mbtiles.all_metadata().unwrap().len(), 3);
+            assert_eq!(
+                mbtiles.vector_layers().unwrap(),
+                Some(vec![json!({"id": "roads", "fields": {}})])
+            );
+
+            // an opened file is read-only
+            assert!(mbtiles.set_metadata("name", "other").is_err());
+            assert!(mbtiles.put_tile((0, 0, 0), b"land").is_err());
+        }
+    }
+
+    #[test]
+    fn test_deduplication() {
+        let path = TempPath::new("dedup");
+        let mut mbtiles = MbTiles::create(&path.0, Schema::Deduplicated).unwrap();
+        let tiles = (0..4).flat_map(|x| (0..4).map(move |y| ((2, x, y), b"ocean")));
+        mbtiles.put_tiles(tiles).unwrap();
+        mbtiles.put_tile((2, 0, 0), b"land").unwrap();
+
+        let conn = mbtiles.into_inner();
+        let count = |sql: &str| -> u32 { conn.query_row(sql, [], |row| row.get(0)).unwrap() };
+        assert_eq!(count("SELECT COUNT(*) FROM map"), 16);
+        assert_eq!(count("SELECT COUNT(*) FROM images"), 2);
+        let tile_id: String = conn
+            .query_row(
+                "SELECT tile_id FROM map WHERE zoom_level = 2 AND tile_column = 1",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(tile_id, "45d20b1d2cc2d52e74b3cbf1750a2e31");
+    }
+
+    #[test]
+    fn test_invalid_file() {
+        let path = TempPath::new("invalid");
+        Connection::open(&path.0)
+            .unwrap()
+            .execute_batch("CREATE TABLE foo (bar TEXT);")
+            .unwrap();
+        assert!(matches!(
+            MbTiles::open(&path.0),
+            Err(Error::InvalidMbTiles { .. })
+        ));
+
+        // opening a missing file fails without creating it
+        let missing = TempPath::new("missing");
+        assert!(matches!(MbTiles::open(&missing.0), Err(Error::Database(_))));
+        assert!(!missing.0.exists());
+
+        let path = TempPath::new("range");
+        let mut mbtiles = MbTiles::create(&path.0, Schema::Flat).unwrap();
+        assert!(mbtiles.put_tile((1, 0, 2), b"").is_err());
+    }
+}
@@ -0,0 +1,228 @@
+//! Merging layers from multiple tiles into one tile.
+
+use indexmap::IndexMap;
+
+use crate::error::Result;
+use crate::geometry::RingClassification;
+use crate::rescale::rescale_geometry;
+use crate::tag::{TagsDecoder, TagsEncoder};
+use crate::vector_tile::Tile;
+use crate::vector_tile::tile::{Feature, GeomType, Layer};
+
+/// Merges tiles for the same tile coordinate into one tile.
+///
+/// Layers with the same name are concatenated in order, keeping the version and extent of the
+/// first occurrence; features from layers with a different extent are rescaled to it, and polygons
+/// from layers with a different version are re-encoded with its winding order. Keys and
+/// values are re-indexed so that each merged layer has deduplicated dictionaries.
+pub fn merge_tiles<'a>(tiles: impl IntoIterator<Item = &'a Tile>) -> Result<Tile> {
+    let mut merged: IndexMap<&str, MergedLayer> = IndexMap::new();
+    for tile in tiles {
+        for layer in &tile.layers {
+            let target = merged.entry(&layer.name).or_insert_with(|| MergedLayer {
+                version: layer.version,
+                extent: layer.extent,
+                features: Vec::new(),
+                tags_enc: TagsEncoder::new(),
+            });
+            target.append(layer)?;
+        }
+    }
+
+    let layers = merged
+        .into_iter()
+        .map(|(name, merged)| {
+            let (keys, values) = merged.tags_enc.into_keys_and_values();
+            Layer {
+                version: merged.version,
+                name: name.to_string(),
+                features: merged.features,
+                keys,
+                values,
+                extent: merged.extent,
+            }
+        })
+        .collect();
+    Ok(Tile { layers })
+}
+
+struct MergedLayer {
+    version: u32,
+    extent: Option<u32>,
+    features: Vec<Feature>,
+    tags_enc: TagsEncoder,
+}
+
+impl MergedLayer {
+    fn append(&mut self, layer: &Layer) -> Result<()> {
+        let from = layer.extent.unwrap_or(4096);
+        let to = self.extent.unwrap_or(4096);
+
+        let rings = RingClassification::for_version(layer.version);
+
+        let tags_dec = TagsDecoder::new(&layer.keys, &layer.values);
+        for feature in &layer.features {
+            // the winding order of polygon rings depends on the version
+            let reencode = from != to
+                || (layer.version != self.version && feature.r#type() == GeomType::Polygon);
+            let geometry = if !reencode {
+                feature.geometry.clone()
+            } else {
+                let geometry =
+                    rescale_geometry(feature.r#type(), &feature.geometry, rings, from, to)?;
+                if geometry.is_empty() {
+                    continue;
+                }
+                geometry
+            };
+            for tag in tags_dec.iter(&feature.tags) {
+                let (key, value) = tag?;
+                self.tags_enc.add(key, value);
+            }
+            self.features.push(Feature {
+                id: feature.id,
+                tags: self.tags_enc.take_tags(),
+                r#type: feature.r#type,
+                geometry,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{GeometryDecoder, GeometryEncoder};
+    use crate::tag::Value;
+
+    fn make_layer(name: &str, extent: u32, features: &[([i32; 2], &str, Value)]) -> Layer {
+        let mut tags_enc = TagsEncoder::new();
+        let features = features
+            .iter()
+            .map(|(point, key, value)| {
+                let mut geom_enc = GeometryEncoder::new();
+                geom_enc.add_points([*point]);
+                tags_enc.add(key, value.clone());
+                Feature {
+                    id: None,
+                    tags: tags_enc.take_tags(),
+                    r#type: Some(GeomType::Point as i32),
+                    geometry: geom_enc.into_vec(),
+                }
+            })
+            .collect();
+        let (keys, values) = tags_enc.into_keys_and_values();
+        Layer {
+            version: 2,
+            name: name.to_string(),
+            features,
+            keys,
+            values,
+            extent: Some(extent),
+        }
+    }
+
+    #[test]
+    fn test_merge_tiles() {
+        let roads = Tile {
+            layers: vec![
+                make_layer(
+                    "roads",
+                    4096,
+                    &[([10, 20], "class", Value::from("primary"))],
+                ),
+                make_layer("pois", 4096, &[([1, 2], "name", Value::from("cafe"))]),
+            ],
+        };
+        let buildings = Tile {
+            layers: vec![
+                make_layer(
+                    "buildings",
+                    4096,
+                    &[([30, 40], "height", Value::from(10u64))],
+                ),
+                make_layer(
+                    "roads",
+                    8192,
+                    &[
+                        ([100, 201], "class", Value::from("primary")),
+                        ([8192, 0], "class", Value::from("service")),
+                    ],
+                ),
+            ],
+        };
+
+        let merged = merge_tiles([&roads, &buildings]).unwrap();
+        let names: Vec<&str> = merged.layers.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(names, ["roads", "pois", "buildings"]);
+
+        let layer = &merged.layers[0];
+        assert_eq!(layer.extent, Some(4096));
+        assert_eq!(layer.features.len(), 3);
+        // dictionaries are deduplicated across the merged layers
+        assert_eq!(layer.keys, ["class"]);
+        assert_eq!(layer.values.len(), 2);
+        assert_eq!(layer.features[0].tags, layer.features[1].tags);
+
+        let points: Vec<_> = layer
+            .features
+            .iter()
+            .map(|f| GeometryDecoder::new(&f.geometry).decode_points().unwrap())
+            .collect();
+        assert_eq!(points, [vec![[10, 20]], vec![[50, 101]], vec![[4096, 0]]]);
+
+        let tags = TagsDecoder::new(&layer.keys, &layer.values)
+            .decode(&layer.features[2].tags)
+            .unwrap();
+        assert_eq!(tags, [("class", Value::from("service"))]);
+    }
+
+    #[test]
+    fn test_merge_versions() {
+        let make_polygon_layer = |version: u32, ring: &[[i32; 2]]| {
+            let mut geom_enc = GeometryEncoder::new();
+            geom_enc.add_ring(ring.iter().copied());
+            Tile {
+                layers: vec![Layer {
+                    version,
+                    name: "water".to_string(),
+                    features: vec![Feature {
+                        id: None,
+                        tags: vec![],
+                        r#type: Some(GeomType::Polygon as i32),
+                        geometry: geom_enc.into_vec(),
+                    }],
+                    keys: vec![],
+                    values: vec![],
+                    extent: Some(4096),
+                }],
+            }
+        };
+        let v2 = make_polygon_layer(2, &[[0, 0], [10, 0], [10, 10], [0, 10]]);
+        // counter-clockwise exterior ring, which version 1 allowed
+        let v1 = make_polygon_layer(1, &[[20, 0], [20, 10], [30, 10], [30, 0]]);
+
+        // the version 1 ring is reversed to become a clockwise exterior ring
+        let merged = merge_tiles([&v2, &v1]).unwrap();
+        let layer = &merged.layers[0];
+        assert_eq!(layer.version, 2);
+        let polygons: Vec<_> = layer
+            .features
+            .iter()
+            .map(|f| GeometryDecoder::new(&f.geometry).decode_polygons().unwrap())
+            .collect();
+        assert_eq!(
+            polygons,
+            [
+                vec![vec![vec![[0, 0], [10, 0], [10, 10], [0, 10]]]],
+                vec![vec![vec![[30, 0], [30, 10], [20, 10], [20, 0]]]],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_empty() {
+        assert_eq!(merge_tiles([]).unwrap(), Tile { layers: vec![] });
+    }
+}
@@ -0,0 +1,286 @@
+//! Extracting a child tile from an ancestor tile (overzooming).
+
+use crate::TileZXY;
+use crate::clip::Clipper;
+use crate::error::{Error, Result};
+use crate::geometry::{GeometryEncoder, RingClassification, transform_geometry};
+use crate::tag::{TagsDecoder, TagsEncoder};
+use crate::tilemath::ancestor;
+use crate::vector_tile::Tile;
+use crate::vector_tile::tile::{Feature, Layer};
+
+/// Cuts the `child` tile out of its ancestor tile `parent`.
+///
+/// Coordinates are scaled and translated into the child's tile space (keeping each layer's extent),
+/// and geometries are clipped to the child tile plus `buffer` (in child tile units). Features that
+/// fall entirely outside are dropped, as are layers left without features. Keys and values are
+/// re-encoded, so those no longer referenced by any feature disappear.
+///
+/// If `child` equals `parent`, the tile is clipped to the buffer and re-encoded.
+pub fn overzoom(tile: &Tile, parent: TileZXY, child: TileZXY, buffer: u32) -> Result<Tile> {
+    if ancestor(child, parent.0) != Some(parent) {
+        return Err(Error::NotDescendant {
+            ancestor: parent,
+            tile: child,
+        });
+    }
+    let dz = child.0 - parent.0;
+    let layers = tile
+        .layers
+        .iter()
+        .map(|layer| overzoom_layer(layer, parent, child, dz, buffer))
+        .filter_map(Result::transpose)
+        .collect::<Result<_>>()?;
+    Ok(Tile { layers })
+}
+
+fn overzoom_layer(
+    layer: &Layer,
+    parent: TileZXY,
+    child: TileZXY,
+    dz: u8,
+    buffer: u32,
+) -> Result<Option<Layer>> {
+    let extent = layer.extent.unwrap_or(4096);
+    let scale = 1i64 << dz;
+    // position of the child within the parent in child units, split into whole parent units and a remainder
+    let offset_x = (child.1 as i64 - ((parent.1 as i64) << dz)) * extent as i64;
+    let offset_y = (child.2 as i64 - ((parent.2 as i64) << dz)) * extent as i64;
+    let (shift_x, rem_x) = (
+        offset_x.div_euclid(scale) as i32,
+        offset_x.rem_euclid(scale),
+    );
+    let (shift_y, rem_y) = (
+        offset_y.div_euclid(scale) as i32,
+        offset_y.rem_euclid(scale),
+    );
+
+    // After shifting by whole parent units, the child covers the same square in parent space on
+    // both axes. Clipping to it (with a margin of one parent unit) before scaling keeps the scaled
+    // coordinates in range.
+    let to_i32 = |v: i64| v.clamp(i32::MIN as i64, i32::MAX as i64) as i32;
+    let min = -(buffer as i64 / scale) - 2;
+    let max = (extent as i64 + buffer as i64) / scale + 2;
+    let pre_clipper = Clipper::from_bounds(to_i32(min), to_i32(max));
+    // Before shifting, the square lies within this one, since the shift is less than the extent.
+    // Clipping to it first keeps the shifted coordinates in range.
+    let shift_clipper = Clipper::from_bounds(to_i32(min), to_i32(extent as i64 + max));
+    let shift = |[x, y]: [i32; 2]| [x - shift_x, y - shift_y];
+    let map = |[x, y]: [i32; 2]| {
+        [
+            (x as i64 * scale - rem_x) as i32,
+            (y as i64 * scale - rem_y) as i32,
+        ]
+    };
+    let clipper = Clipper::new(extent, buffer);
+
+    let tags_dec = TagsDecoder::new(&layer.keys, &layer.values);
+    let mut tags_enc = TagsEncoder::new();
+    let mut features = Vec::new();
+    for feature in &layer.features {
+        let geom_type = feature.r#type();
+        let shifted = transform_geometry(
+            geom_type,
+            &feature.geometry,
+            RingClassification::for_version(layer.version),
+            Some(shift_clipper),
+            shift,
+            GeometryEncoder::new(),
+        )?;
+        let geometry = transform_geometry(
+            geom_type,
+            &shifted,
+            RingClassification::Winding,
+            Some(pre_clipper),
+            map,
+            GeometryEncoder::with_clipper(clipper),
+        )?;
+        if geometry.is_empty() {
+            continue;
+        }
+        for tag in tags_dec.iter(&feature.tags) {
+            let (key, value) = tag?;
+            tags_enc.add(key, value);
+        }
+        features.push(Feature {
+            id: feature.id,
+            tags: tags_enc.take_tags(),
+            r#type: Some(geom_type as i32),
+            geometry,
+        });
+    }
+    if features.is_empty() {
+        return Ok(None);
+    }
+
+    let (keys, values) = tags_enc.into_keys_and_values();
+    Ok(Some(Layer {
+        version: layer.version,
+        name: layer.name.clone(),
+        features,
+        keys,
+        values,
+        extent: layer.extent,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::GeometryDecoder;
+    use crate::tag::Value;
+    use crate::vector_tile::tile::GeomType;
+
+    fn make_tile() -> Tile {
+        let mut tags_enc = TagsEncoder::new();
+        let mut features = Vec::new();
+
+        // a point in the north-west quadrant, a line crossing the whole tile,
+        // and a square in the south-east quadrant
+        let mut geom_enc = GeometryEncoder::new();
+        geom_enc.add_points([[1000, 1000]]);
+        tags_enc.add("kind", "point");
+        features.push(Feature {
+            id: Some(1),
+            tags: tags_enc.take_tags(),
+            r#type: Some(GeomType::Point as i32),
+            geometry: geom_enc.into_vec(),
+        });
+
+        let mut geom_enc = GeometryEncoder::new();
+        geom_enc.add_linestring([[0, 1000], [4096, 1000]]);
+        tags_enc.add("kind", "line");
+        features.push(Feature {
+            id: Some(2),
+            tags: tags_enc.take_tags(),
+            r#type: Some(GeomType::Linestring as i32),
+            geometry: geom_enc.into_vec(),
+        });
+
+        let mut geom_enc = GeometryEncoder::new();
+        geom_enc.add_polygon([[[2500, 2500], [3500, 2500], [3500, 3500], [2500, 3500]]]);
+        tags_enc.add("kind", "polygon");
+        tags_enc.add("area", 1000000u32);
+        features.push(Feature {
+            id: Some(3),
+            tags: tags_enc.take_tags(),
+            r#type: Some(GeomType::Polygon as i32),
+            geometry: geom_enc.into_vec(),
+        });
+
+        let (keys, values) = tags_enc.into_keys_and_values();
+        Tile {
+            layers: vec![Layer {
+                version: 2,
+                name: "test".to_string(),
+                features,
+                keys,
+                values,
+                extent: Some(4096),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_overzoom_north_west() {
+        let tile = overzoom(&make_tile(), (10, 100, 200), (11, 200, 400), 0).unwrap();
+        let layer = &tile.layers[0];
+        assert_eq!(layer.features.len(), 2);
+
+        let point = &layer.features[0];
+        assert_eq!(point.id, Some(1));
+        let points = GeometryDecoder::new(&point.geometry)
+            .decode_points()
+            .unwrap();
+        assert_eq!(points, [[2000, 2000]]);
+
+        let line = &layer.features[1];
+        let lines = GeometryDecoder::new(&line.geometry)
+            .decode_linestrings()
+            .unwrap();
+        assert_eq!(lines, [vec![[0, 2000], [4096, 2000]]]);
+
+        // the polygon's keys and values are gone
+        assert_eq!(layer.keys, ["kind"]);
+        assert_eq!(layer.values.len(), 2);
+        let tags = TagsDecoder::new(&layer.keys, &layer.values)
+            .decode(&line.tags)
+            .unwrap();
+        assert_eq!(tags, [("kind", Value::from("line"))]);
+    }
+
+    #[test]
+    fn test_overzoom_south_east_with_buffer() {
+        let tile = overzoom(&make_tile(), (10, 100, 200), (12, 403, 803), 64).unwrap();
+        let layer = &tile.layers[0];
+        assert_eq!(layer.features.len(), 1);
+        let polygons = GeometryDecoder::new(&layer.features[0].geometry)
+            .decode_polygons()
+            .unwrap();
+        // the square spans [2500, 3500] in the parent, i.e. [-2288, 1712] in the child,
+        // clipped to [-64, 4160]
+        assert_eq!(polygons.len(), 1);
+        let ring = &polygons[0][0];
+        assert!(
+            ring.iter()
+                .all(|&[x, y]| (-64..=4160).contains(&x) && (-64..=4160).contains(&y))
+        );
+        assert!(ring.contains(&[-64, -64]));
+        assert!(ring.contains(&[1712, 1712]));
+        assert_eq!(layer.keys, ["kind", "area"]);
+    }
+
+    #[test]
+    fn test_overzoom_empty_and_invalid() {
+        // the child in the north-east quadrant only contains part of the line
+        let tile = overzoom(&make_tile(), (10, 100, 200), (11, 201, 400), 0).unwrap();
+        assert_eq!(tile.layers[0].features.len(), 1);
+
+        // nothing in the south-west quadrant
+        let tile = overzoom(&make_tile(), (10, 100, 200), (12, 400, 803), 0).unwrap();
+        assert!(tile.layers.is_empty());
+
+        assert_eq!(
+            overzoom(&make_tile(), (10, 100, 200), (11, 202, 400), 0),
+            Err(Error::NotDescendant {
+                ancestor: (10, 100, 200),
+                tile: (11, 202, 400)
+            })
+        );
+    }
+
+    #[test]
+    fn test_overzoom_extreme_coordinates() {
+        let mut geom_enc = GeometryEncoder::new();
+        geom_enc.add_points([[i32::MIN, i32::MIN]]);
+        let tile = Tile {
+            layers: vec![Layer {
+                version: 2,
+                name: "test".to_string(),
+                features: vec![Feature {
+                    id: None,
+                    tags: vec![],
+                    r#type: Some(GeomType::Point as i32),
+                    geometry: geom_enc.into_vec(),
+                }],
+                keys: vec![],
+                values: vec![],
+                extent: Some(4096),
+            }],
+        };
+        let tile = overzoom(&tile, (0, 0, 0), (1, 1, 1), 64).unwrap();
+        assert!(tile.layers.is_empty());
+    }
+
+    #[test]
+    fn test_overzoom_deep() {
+        // large zoom differences don't overflow, even far from the diagonal
+        let tile = overzoom(&make_tile(), (0, 0, 0), (20, (1 << 20) - 1, 1000 << 8), 64).unwrap();
+        let layer = &tile.layers[0];
+        assert_eq!(layer.features.len(), 1);
+        let lines = GeometryDecoder::new(&layer.features[0].geometry)
+            .decode_linestrings()
+            .unwrap();
+        assert_eq!(lines, [vec![[-64, 0], [4096, 0]]]);
+    }
+}
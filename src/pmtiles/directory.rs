@@ -0,0 +1,201 @@
+//! Directories of a PMTiles archive, mapping tile IDs to byte ranges.
+
+use super::invalid;
+use crate::error::{Error, Result};
+
+/// An entry of a directory.
+///
+/// An entry with `run_length == 0` points to a leaf directory; otherwise it points to tile data
+/// shared by `run_length` consecutive tile IDs starting at `tile_id`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    pub tile_id: u64,
+    pub offset: u64,
+    pub length: u32,
+    pub run_length: u32,
+}
+
+impl Entry {
+    /// Returns true if the entry points to a leaf directory.
+    #[inline]
+    pub fn is_leaf(&self) -> bool {
+        self.run_length == 0
+    }
+}
+
+/// Decodes an (already decompressed) directory.
+pub fn decode_directory(buf: &[u8]) -> Result<Vec<Entry>> {
+    let mut pos = 0;
+    let mut next = || read_varint(buf, &mut pos);
+
+    let num_entries = next()? as usize;
+    if num_entries > buf.len() {
+        return Err(invalid("too many directory entries"));
+    }
+    let mut entries = vec![
+        Entry {
+            tile_id: 0,
+            offset: 0,
+            length: 0,
+            run_length: 0,
+        };
+        num_entries
+    ];
+
+    let mut last_id = 0u64;
+    for entry in entries.iter_mut() {
+        last_id = last_id
+            .checked_add(next()?)
+            .ok_or_else(|| invalid("tile ID overflow"))?;
+        entry.tile_id = last_id;
+    }
+    for entry in entries.iter_mut() {
+        entry.run_length = to_u32(next()?)?;
+    }
+    for entry in entries.iter_mut() {
+        entry.length = to_u32(next()?)?;
+    }
+    for i in 0..num_entries {
+        let value = next()?;
+        entries[i].offset = match (value, i.checked_sub(1)) {
+            (0, Some(prev)) => entries[prev]
+                .offset
+                .checked_add(entries[prev].length as u64)
+                .ok_or_else(|| invalid("entry offset overflow"))?,
+            (0, None) => return Err(invalid("first entry has no offset")),
+            (value, _) => value - 1,
+        };
+    }
+    Ok(entries)
+}
+
+/// Encodes a directory (without compression).
+///
+/// Entries must be sorted by tile ID. Offsets contiguous with the previous entry are
+/// stored as zero, as recommended by the specification.
+pub fn encode_directory(entries: &[Entry]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(entries.len() * 4 + 4);
+    put_varint(&mut buf, entries.len() as u64);
+    let mut last_id = 0;
+    for entry in entries {
+        put_varint(&mut buf, entry.tile_id - last_id);
+        last_id = entry.tile_id;
+    }
+    for entry in entries {
+        put_varint(&mut buf, entry.run_length as u64);
+    }
+    for entry in entries {
+        put_varint(&mut buf, entry.length as u64);
+    }
+    for (i, entry) in entries.iter().enumerate() {
+        match i.checked_sub(1).map(|j| &entries[j]) {
+            Some(prev) if entry.offset == prev.offset + prev.length as u64 => buf.push(0),
+            _ => put_varint(&mut buf, entry.offset + 1),
+        }
+    }
+    buf
+}
+
+/// Finds the entry that contains the tile ID, or the leaf directory that may contain it.
+pub fn find_entry(entries: &[Entry], tile_id: u64) -> Option<&Entry> {
+    let idx = entries.partition_point(|e| e.tile_id <= tile_id);
+    let entry = entries.get(idx.checked_sub(1)?)?;
+    if entry.is_leaf() || tile_id - entry.tile_id < entry.run_length as u64 {
+        Some(entry)
+    } else {
+        None
+    }
+}
+
+fn read_varint(buf: &[u8], pos: &mut usize) -> Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let Some(&byte) = buf.get(*pos) else {
+            return Err(Error::Truncated { offset: *pos });
+        };
+        *pos += 1;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(Error::InvalidVarint { offset: *pos })
+}
+
+#[inline]
+fn put_varint(buf: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        buf.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    buf.push(v as u8);
+}
+
+#[inline]
+fn to_u32(value: u64) -> Result<u32> {
+    u32::try_from(value).map_err(|_| invalid("directory value exceeds 32 bits"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_directory() {
+        // 3 entries: tile IDs 0, 1, 5; run lengths 1, 3, 0 (leaf); lengths 10, 20, 30;
+        // offsets 0, contiguous (0 => previous offset + length), 100
+        let buf = [3, 0, 1, 4, 1, 3, 0, 10, 20, 30, 1, 0, 101];
+        let entries = decode_directory(&buf).unwrap();
+        assert_eq!(
+            entries,
+            [
+                Entry {
+                    tile_id: 0,
+                    offset: 0,
+                    length: 10,
+                    run_length: 1
+                },
+                Entry {
+                    tile_id: 1,
+                    offset: 10,
+                    length: 20,
+                    run_length: 3
+                },
+                Entry {
+                    tile_id: 5,
+                    offset: 100,
+                    length: 30,
+                    run_length: 0
+                },
+            ]
+        );
+
+        assert_eq!(find_entry(&entries, 0).unwrap().tile_id, 0);
+        assert_eq!(find_entry(&entries, 3).unwrap().tile_id, 1);
+        assert_eq!(find_entry(&entries, 4), None);
+        assert!(find_entry(&entries, 100).unwrap().is_leaf());
+
+        assert_eq!(
+            decode_directory(&buf[..8]),
+            Err(Error::Truncated { offset: 8 })
+        );
+    }
+
+    #[test]
+    fn test_decode_malformed_directory() {
+        // the first entry has no previous entry to be contiguous with
+        assert!(matches!(
+            decode_directory(&[1, 0, 1, 10, 0]),
+            Err(Error::InvalidPmTiles { .. })
+        ));
+
+        // the second offset overflows: u64::MAX - 1 + 10
+        let mut buf = vec![2, 0, 1, 1, 1, 10, 10];
+        buf.extend([0xff; 9]);
+        buf.extend([0x01, 0]);
+        assert!(matches!(
+            decode_directory(&buf),
+            Err(Error::InvalidPmTiles { .. })
+        ));
+    }
+}
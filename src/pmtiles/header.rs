@@ -0,0 +1,227 @@
+//! The fixed-size header of a PMTiles archive.
+
+use super::invalid;
+use crate::error::Result;
+
+/// Length of the header in bytes.
+pub const HEADER_LEN: usize = 127;
+
+const MAGIC: &[u8; 7] = b"PMTiles";
+
+/// Compression of directories, metadata or tiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Compression {
+    Unknown = 0,
+    None = 1,
+    Gzip = 2,
+    Brotli = 3,
+    Zstd = 4,
+}
+
+impl TryFrom<u8> for Compression {
+    type Error = crate::Error;
+
+    fn try_from(value: u8) -> Result<Self> {
+        Ok(match value {
+            0 => Compression::Unknown,
+            1 => Compression::None,
+            2 => Compression::Gzip,
+            3 => Compression::Brotli,
+            4 => Compression::Zstd,
+            _ => return Err(invalid("unknown compression type")),
+        })
+    }
+}
+
+/// Type of the tiles in the archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum TileType {
+    Unknown = 0,
+    Mvt = 1,
+    Png = 2,
+    Jpeg = 3,
+    Webp = 4,
+    Avif = 5,
+}
+
+impl TryFrom<u8> for TileType {
+    type Error = crate::Error;
+
+    fn try_from(value: u8) -> Result<Self> {
+        Ok(match value {
+            0 => TileType::Unknown,
+            1 => TileType::Mvt,
+            2 => TileType::Png,
+            3 => TileType::Jpeg,
+            4 => TileType::Webp,
+            5 => TileType::Avif,
+            _ => return Err(invalid("unknown tile type")),
+        })
+    }
+}
+
+/// PMTiles v3 header.
+///
+/// Offsets are absolute byte offsets in the archive. Positions are in degrees.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Header {
+    pub root_directory_offset: u64,
+    pub root_directory_length: u64,
+    pub metadata_offset: u64,
+    pub metadata_length: u64,
+    pub leaf_directories_offset: u64,
+    pub leaf_directories_length: u64,
+    pub tile_data_offset: u64,
+    pub tile_data_length: u64,
+    pub addressed_tiles_count: u64,
+    pub tile_entries_count: u64,
+    pub tile_contents_count: u64,
+    pub clustered: bool,
+    pub internal_compression: Compression,
+    pub tile_compression: Compression,
+    pub tile_type: TileType,
+    pub min_zoom: u8,
+    pub max_zoom: u8,
+    /// (min_lon, min_lat, max_lon, max_lat)
+    pub bounds: [f64; 4],
+    pub center_zoom: u8,
+    /// (lon, lat)
+    pub center: [f64; 2],
+}
+
+impl Header {
+    /// Parses the header from the first [`HEADER_LEN`] bytes of an archive.
+    pub fn from_bytes(buf: &[u8]) -> Result<Self> {
+        if buf.len() < HEADER_LEN {
+            return Err(invalid("header is too short"));
+        }
+        if &buf[0..7] != MAGIC {
+            return Err(invalid("missing PMTiles magic number"));
+        }
+        if buf[7] != 3 {
+            return Err(invalid("only spec version 3 is supported"));
+        }
+
+        let u64_at = |pos: usize| u64::from_le_bytes(buf[pos..pos + 8].try_into().unwrap());
+        let e7_at =
+            |pos: usize| i32::from_le_bytes(buf[pos..pos + 4].try_into().unwrap()) as f64 / 1e7;
+        Ok(Self {
+            root_directory_offset: u64_at(8),
+            root_directory_length: u64_at(16),
+            metadata_offset: u64_at(24),
+            metadata_length: u64_at(32),
+            leaf_directories_offset: u64_at(40),
+            leaf_directories_length: u64_at(48),
+            tile_data_offset: u64_at(56),
+            tile_data_length: u64_at(64),
+            addressed_tiles_count: u64_at(72),
+            tile_entries_count: u64_at(80),
+            tile_contents_count: u64_at(88),
+            clustered: buf[96] == 1,
+            internal_compression: buf[97].try_into()?,
+            tile_compression: buf[98].try_into()?,
+            tile_type: buf[99].try_into()?,
+            min_zoom: buf[100],
+            max_zoom: buf[101],
+            bounds: [e7_at(102), e7_at(106), e7_at(110), e7_at(114)],
+            center_zoom: buf[118],
+            center: [e7_at(119), e7_at(123)],
+        })
+    }
+
+    /// Serializes the header.
+    pub fn to_bytes(&self) -> [u8; HEADER_LEN] {
+        let mut buf = [0u8; HEADER_LEN];
+        buf[0..7].copy_from_slice(MAGIC);
+        buf[7] = 3;
+        for (i, v) in [
+            self.root_directory_offset,
+            self.root_directory_length,
+            self.metadata_offset,
+            self.metadata_length,
+            self.leaf_directories_offset,
+            self.leaf_directories_length,
+            self.tile_data_offset,
+            self.tile_data_length,
+            self.addressed_tiles_count,
+            self.tile_entries_count,
+            self.tile_contents_count,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            buf[8 + i * 8..16 + i * 8].copy_from_slice(&v.to_le_bytes());
+        }
+        buf[96] = self.clustered as u8;
+        buf[97] = self.internal_compression as u8;
+        buf[98] = self.tile_compression as u8;
+        buf[99] = self.tile_type as u8;
+        buf[100] = self.min_zoom;
+        buf[101] = self.max_zoom;
+        let e7 = |v: f64| ((v * 1e7).round() as i32).to_le_bytes();
+        for (i, &v) in self.bounds.iter().enumerate() {
+            buf[102 + i * 4..106 + i * 4].copy_from_slice(&e7(v));
+        }
+        buf[118] = self.center_zoom;
+        buf[119..123].copy_from_slice(&e7(self.center[0]));
+        buf[123..127].copy_from_slice(&e7(self.center[1]));
+        buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    #[test]
+    fn test_roundtrip() {
+        let header = Header {
+            root_directory_offset: 127,
+            root_directory_length: 30,
+            metadata_offset: 157,
+            metadata_length: 40,
+            leaf_directories_offset: 197,
+            leaf_directories_length: 0,
+            tile_data_offset: 197,
+            tile_data_length: 1000,
+            addressed_tiles_count: 10,
+            tile_entries_count: 8,
+            tile_contents_count: 5,
+            clustered: true,
+            internal_compression: Compression::Gzip,
+            tile_compression: Compression::None,
+            tile_type: TileType::Mvt,
+            min_zoom: 0,
+            max_zoom: 14,
+            bounds: [139.5, 35.25, 140.125, 36.0],
+            center_zoom: 7,
+            center: [139.75, -35.5],
+        };
+        assert_eq!(Header::from_bytes(&header.to_bytes()).unwrap(), header);
+    }
+
+    #[test]
+    fn test_invalid_header() {
+        assert!(matches!(
+            Header::from_bytes(b"PMTiles\x03"),
+            Err(Error::InvalidPmTiles { .. })
+        ));
+
+        let mut buf = [0u8; HEADER_LEN];
+        buf[..7].copy_from_slice(b"MBTiles");
+        assert!(matches!(
+            Header::from_bytes(&buf),
+            Err(Error::InvalidPmTiles { .. })
+        ));
+
+        buf[..7].copy_from_slice(MAGIC);
+        buf[7] = 2;
+        assert!(matches!(
+            Header::from_bytes(&buf),
+            Err(Error::InvalidPmTiles { .. })
+        ));
+    }
+}
@@ -0,0 +1,57 @@
+//! Reading and writing PMTiles v3 archives.
+//!
+//! See the [PMTiles specification](https://github.com/protomaps/PMTiles/blob/main/spec/v3/spec.md).
+
+pub mod directory;
+pub mod header;
+pub mod reader;
+pub mod writer;
+
+pub use directory::Entry;
+pub use header::{Compression, HEADER_LEN, Header, TileType};
+pub use reader::PmTilesReader;
+pub use writer::PmTilesWriter;
+
+use std::io::{Read, Write};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+
+use crate::error::{Error, Result};
+
+/// Decompresses data with the given compression.
+pub(crate) fn decompress(data: Vec<u8>, compression: Compression) -> Result<Vec<u8>> {
+    match compression {
+        Compression::Unknown | Compression::None => Ok(data),
+        Compression::Gzip => {
+            let mut out = Vec::with_capacity(data.len() * 2);
+            GzDecoder::new(&data[..]).read_to_end(&mut out)?;
+            Ok(out)
+        }
+        other => Err(Error::UnsupportedCompression {
+            compression: other as u8,
+        }),
+    }
+}
+
+/// Compresses data with the given compression.
+pub(crate) fn compress(data: &[u8], compression: Compression) -> Result<Vec<u8>> {
+    match compression {
+        Compression::Unknown | Compression::None => Ok(data.to_vec()),
+        Compression::Gzip => {
+            let mut enc = GzEncoder::new(Vec::new(), flate2::Compression::default());
+            enc.write_all(data)?;
+            Ok(enc.finish()?)
+        }
+        other => Err(Error::UnsupportedCompression {
+            compression: other as u8,
+        }),
+    }
+}
+
+#[inline]
+pub(crate) fn invalid(reason: &str) -> Error {
+    Error::InvalidPmTiles {
+        reason: reason.to_string(),
+    }
+}
@@ -0,0 +1,257 @@
+//! Random access to tiles in a PMTiles archive.
+
+use std::io::{Read, Seek, SeekFrom};
+
+use super::directory::{Entry, decode_directory, find_entry};
+use super::header::{HEADER_LEN, Header};
+use super::{decompress, invalid};
+use crate::TileZXY;
+use crate::error::Result;
+use crate::tileid::zxy_to_hilbert;
+
+/// Maximum depth of leaf directories, as recommended by the specification.
+const MAX_DEPTH: usize = 3;
+
+/// Reader for a PMTiles v3 archive.
+///
+/// Works with any `Read + Seek` source, such as a [`File`](std::fs::File) or a
+/// [`Cursor`](std::io::Cursor) over in-memory or memory-mapped bytes.
+pub struct PmTilesReader<R> {
+    source: R,
+    header: Header,
+    root: Vec<Entry>,
+}
+
+impl<R: Read + Seek> PmTilesReader<R> {
+    /// Opens an archive by reading its header and root directory.
+    pub fn new(mut source: R) -> Result<Self> {
+        let mut buf = [0u8; HEADER_LEN];
+        source.seek(SeekFrom::Start(0))?;
+        source.read_exact(&mut buf)?;
+        let header = Header::from_bytes(&buf)?;
+
+        let mut reader = Self {
+            source,
+            header,
+            root: Vec::new(),
+        };
+        reader.root = reader.read_directory(
+            reader.header.root_directory_offset,
+            reader.header.root_directory_length,
+        )?;
+        Ok(reader)
+    }
+
+    /// Returns the header of the archive.
+    #[inline]
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Reads the (decompressed) JSON metadata.
+    pub fn metadata(&mut self) -> Result<String> {
+        let data = self.read_range(self.header.metadata_offset, self.header.metadata_length)?;
+        let data = decompress(data, self.header.internal_compression)?;
+        String::from_utf8(data).map_err(|_| invalid("metadata is not valid UTF-8"))
+    }
+
+    /// Reads a tile and decompresses it according to the header's tile compression.
+    ///
+    /// Returns `None` if the archive doesn't contain the tile.
+    pub fn get_tile(&mut self, tile: TileZXY) -> Result<Option<Vec<u8>>> {
+        match self.get_tile_raw(tile)? {
+            Some(data) => Ok(Some(decompress(data, self.header.tile_compression)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads a tile as stored in the archive, without decompression.
+    pub fn get_tile_raw(&mut self, (z, x, y): TileZXY) -> Result<Option<Vec<u8>>> {
+        if z > 31 || x >= 1 << z || y >= 1 << z {
+            return Ok(None);
+        }
+        let tile_id = zxy_to_hilbert(z, x, y);
+
+        let mut entry = match find_entry(&self.root, tile_id) {
+            Some(entry) => *entry,
+            None => return Ok(None),
+        };
+        for _ in 0..MAX_DEPTH {
+            if !entry.is_leaf() {
+                break;
+            }
+            let offset = checked_offset(self.header.leaf_directories_offset, entry.offset)?;
+            let leaf = self.read_directory(offset, entry.length as u64)?;
+            entry = match find_entry(&leaf, tile_id) {
+                Some(entry) => *entry,
+                None => return Ok(None),
+            };
+        }
+        if entry.is_leaf() {
+            return Err(invalid("leaf directories are nested too deeply"));
+        }
+
+        let offset = checked_offset(self.header.tile_data_offset, entry.offset)?;
+        let data = self.read_range(offset, entry.length as u64)?;
+        Ok(Some(data))
+    }
+
+    /// Consumes the reader and returns the underlying source.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.source
+    }
+
+    fn read_directory(&mut self, offset: u64, length: u64) -> Result<Vec<Entry>> {
+        let data = self.read_range(offset, length)?;
+        decode_directory(&decompress(data, self.header.internal_compression)?)
+    }
+
+    fn read_range(&mut self, offset: u64, length: u64) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        self.source.seek(SeekFrom::Start(offset))?;
+        (&mut self.source).take(length).read_to_end(&mut buf)?;
+        if (buf.len() as u64) < length {
+            return Err(invalid("archive is truncated"));
+        }
+        Ok(buf)
+    }
+}
+
+/// Adds an entry offset to a section offset from the header.
+#[inline]
+fn checked_offset(section_offset: u64, entry_offset: u64) -> Result<u64> {
+    section_offset
+        .checked_add(entry_offset)
+        .ok_or_else(|| invalid("entry offset overflow"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::error::Error;
+    use crate::pmtiles::directory::encode_directory;
+    use crate::pmtiles::{Compression, TileType, compress};
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        compress(data, Compression::Gzip).unwrap()
+    }
+
+    /// Builds an archive with a root directory pointing to one leaf directory.
+    fn build_archive(tiles: &[Vec<u8>]) -> Vec<u8> {
+        let mut tile_data = Vec::new();
+        let mut leaf_entries = Vec::new();
+        for (i, tile) in tiles.iter().enumerate() {
+            let data = gzip(tile);
+            leaf_entries.push(Entry {
+                tile_id: i as u64 * 2,
+                offset: tile_data.len() as u64,
+                length: data.len() as u32,
+                run_length: 2,
+            });
+            tile_data.extend(data);
+        }
+        let leaf = gzip(&encode_directory(&leaf_entries));
+        let root = gzip(&encode_directory(&[Entry {
+            tile_id: 0,
+            offset: 0,
+            length: leaf.len() as u32,
+            run_length: 0,
+        }]));
+        let metadata = gzip(br#"{"name":"test"}"#);
+
+        let root_directory_offset = HEADER_LEN as u64;
+        let metadata_offset = root_directory_offset + root.len() as u64;
+        let leaf_directories_offset = metadata_offset + metadata.len() as u64;
+        let tile_data_offset = leaf_directories_offset + leaf.len() as u64;
+        let header = Header {
+            root_directory_offset,
+            root_directory_length: root.len() as u64,
+            metadata_offset,
+            metadata_length: metadata.len() as u64,
+            leaf_directories_offset,
+            leaf_directories_length: leaf.len() as u64,
+            tile_data_offset,
+            tile_data_length: tile_data.len() as u64,
+            addressed_tiles_count: tiles.len() as u64 * 2,
+            tile_entries_count: tiles.len() as u64,
+            tile_contents_count: tiles.len() as u64,
+            clustered: true,
+            internal_compression: Compression::Gzip,
+            tile_compression: Compression::Gzip,
+            tile_type: TileType::Mvt,
+            min_zoom: 0,
+            max_zoom: 3,
+            bounds: [-180.0, -85.0, 180.0, 85.0],
+            center_zoom: 0,
+            center: [0.0, 0.0],
+        };
+
+        let mut buf = header.to_bytes().to_vec();
+        buf.extend(root);
+        buf.extend(metadata);
+        buf.extend(leaf);
+        buf.extend(tile_data);
+        buf
+    }
+
+    #[test]
+    fn test_read_archive() {
+        let tiles: Vec<Vec<u8>> = (0..10).map(|i| vec![i; 100]).collect();
+        let archive = build_archive(&tiles);
+        let mut reader = PmTilesReader::new(Cursor::new(archive)).unwrap();
+
+        let header = reader.header();
+        assert!(header.clustered);
+        assert_eq!(header.internal_compression, Compression::Gzip);
+        assert_eq!(header.tile_compression, Compression::Gzip);
+        assert_eq!(header.tile_type, TileType::Mvt);
+        assert_eq!(header.max_zoom, 3);
+        assert_eq!(header.bounds, [-180.0, -85.0, 180.0, 85.0]);
+
+        assert_eq!(reader.metadata().unwrap(), r#"{"name":"test"}"#);
+
+        for tile_id in 0..20 {
+            let tile = crate::tileid::hilbert_to_zxy(tile_id);
+            let expected = &tiles[tile_id as usize / 2];
+            assert_eq!(reader.get_tile(tile).unwrap().as_ref(), Some(expected));
+        }
+        let raw = reader.get_tile_raw((0, 0, 0)).unwrap().unwrap();
+        assert_eq!(&raw[..2], [0x1f, 0x8b]);
+
+        assert_eq!(
+            reader.get_tile(crate::tileid::hilbert_to_zxy(20)).unwrap(),
+            None
+        );
+        assert_eq!(reader.get_tile((1, 2, 0)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_truncated_archive() {
+        let archive = build_archive(&[vec![1; 100]]);
+        let truncated = archive[..archive.len() - 10].to_vec();
+        let mut reader = PmTilesReader::new(Cursor::new(truncated)).unwrap();
+        assert!(reader.get_tile((0, 0, 0)).is_err());
+
+        let err = PmTilesReader::new(Cursor::new(&archive[..100]))
+            .err()
+            .unwrap();
+        assert!(matches!(&err, Error::Io(e) if e.kind() == std::io::ErrorKind::UnexpectedEof));
+        assert!(std::error::Error::source(&err).is_some());
+    }
+
+    #[test]
+    fn test_offset_overflow() {
+        let mut archive = build_archive(&[vec![1; 100]]);
+        let mut header = Header::from_bytes(&archive[..HEADER_LEN]).unwrap();
+        header.tile_data_offset = u64::MAX;
+        archive[..HEADER_LEN].copy_from_slice(&header.to_bytes());
+        let mut reader = PmTilesReader::new(Cursor::new(archive)).unwrap();
+        assert!(matches!(
+            reader.get_tile((0, 0, 0)),
+            Err(Error::InvalidPmTiles { .. })
+        ));
+    }
+}
@@ -0,0 +1,306 @@
+//! Building a PMTiles archive from tiles given in any order.
+
+use std::io::Write;
+
+use foldhash::HashMap;
+use sha2::{Digest, Sha256};
+
+use super::directory::{Entry, encode_directory};
+use super::header::{Compression, HEADER_LEN, Header, TileType};
+use super::{compress, invalid};
+use crate::TileZXY;
+use crate::error::Result;
+use crate::tileid::zxy_to_hilbert;
+
+/// The header and root directory must fit in the first 16 KiB of the archive.
+const MAX_ROOT_LEN: usize = 16384 - HEADER_LEN;
+
+/// Initial number of entries per leaf directory.
+const LEAF_SIZE: usize = 4096;
+
+/// Writer for a PMTiles v3 archive.
+///
+/// Tiles are buffered in memory and may be added in any order. Identical tile contents
+/// are stored only once, and consecutive tile IDs with the same content are merged into
+/// run-length entries. Directories and metadata are gzip-compressed.
+pub struct PmTilesWriter {
+    tile_type: TileType,
+    tile_compression: Compression,
+    metadata: String,
+    bounds: [f64; 4],
+    center: Option<(u8, [f64; 2])>,
+    /// (tile ID, index into `contents`)
+    tiles: Vec<(u64, usize)>,
+    /// Compressed tile contents.
+    contents: Vec<Vec<u8>>,
+    /// Maps SHA-256 digests of uncompressed tile contents to their index in `contents`.
+    content_index: HashMap<[u8; 32], usize>,
+}
+
+impl PmTilesWriter {
+    /// Creates a writer. Tiles are compressed with `tile_compression` when added.
+    pub fn new(tile_type: TileType, tile_compression: Compression) -> Self {
+        Self {
+            tile_type,
+            tile_compression,
+            metadata: "{}".to_string(),
+            bounds: [-180.0, -85.0511287, 180.0, 85.0511287],
+            center: None,
+            tiles: Vec::new(),
+            contents: Vec::new(),
+            content_index: HashMap::default(),
+        }
+    }
+
+    /// Sets the JSON metadata (defaults to `{}`).
+    #[inline]
+    pub fn set_metadata(&mut self, json: &str) {
+        self.metadata = json.to_string();
+    }
+
+    /// Sets the bounds as (min_lon, min_lat, max_lon, max_lat). Defaults to the whole world.
+    #[inline]
+    pub fn set_bounds(&mut self, bounds: [f64; 4]) {
+        self.bounds = bounds;
+    }
+
+    /// Sets the center zoom and position (lon, lat).
+    ///
+    /// Defaults to the center of the bounds at the minimum zoom level.
+    #[inline]
+    pub fn set_center(&mut self, zoom: u8, center: [f64; 2]) {
+        self.center = Some((zoom, center));
+    }
+
+    /// Adds an uncompressed tile. If the same tile is added more than once, the last one wins.
+    pub fn add_tile(&mut self, (z, x, y): TileZXY, data: &[u8]) -> Result<()> {
+        if z > 31 || x >= 1 << z || y >= 1 << z {
+            return Err(invalid("tile coordinate out of range"));
+        }
+        let digest: [u8; 32] = Sha256::digest(data).into();
+        let index = match self.content_index.get(&digest) {
+            Some(&index) => index,
+            None => {
+                let index = self.contents.len();
+                self.contents.push(compress(data, self.tile_compression)?);
+                self.content_index.insert(digest, index);
+                index
+            }
+        };
+        self.tiles.push((zxy_to_hilbert(z, x, y), index));
+        Ok(())
+    }
+
+    /// Writes the archive and returns its header.
+    pub fn write<W: Write>(mut self, mut out: W) -> Result<Header> {
+        // stable sort, then keep the last of each duplicate tile ID
+        self.tiles.sort_by_key(|&(tile_id, _)| tile_id);
+        self.tiles.dedup_by(|next, prev| {
+            if next.0 == prev.0 {
+                prev.1 = next.1;
+                true
+            } else {
+                false
+            }
+        });
+
+        // lay out tile data in tile ID order of first use, so the archive is clustered
+        let mut offsets: Vec<Option<u64>> = vec![None; self.contents.len()];
+        let mut data_order = Vec::new();
+        let mut tile_data_length = 0u64;
+        let mut entries: Vec<Entry> = Vec::new();
+        for &(tile_id, index) in &self.tiles {
+            let offset = *offsets[index].get_or_insert_with(|| {
+                let offset = tile_data_length;
+                tile_data_length += self.contents[index].len() as u64;
+                data_order.push(index);
+                offset
+            });
+            if let Some(last) = entries.last_mut()
+                && last.offset == offset
+                && last.tile_id + last.run_length as u64 == tile_id
+                && last.run_length < u32::MAX
+            {
+                last.run_length += 1;
+                continue;
+            }
+            let length = u32::try_from(self.contents[index].len())
+                .map_err(|_| invalid("tile is larger than 4 GiB"))?;
+            entries.push(Entry {
+                tile_id,
+                offset,
+                length,
+                run_length: 1,
+            });
+        }
+
+        let (root, leaves) = build_directories(&entries)?;
+        let metadata = compress(self.metadata.as_bytes(), Compression::Gzip)?;
+
+        let zooms = || {
+            self.tiles
+                .iter()
+                .map(|&(tile_id, _)| crate::tileid::hilbert_to_zxy(tile_id).0)
+        };
+        let min_zoom = zooms().min().unwrap_or(0);
+        let max_zoom = zooms().max().unwrap_or(0);
+        let [min_lon, min_lat, max_lon, max_lat] = self.bounds;
+        let (center_zoom, center) = self.center.unwrap_or((
+            min_zoom,
+            [(min_lon + max_lon) / 2.0, (min_lat + max_lat) / 2.0],
+        ));
+
+        let root_directory_offset = HEADER_LEN as u64;
+        let metadata_offset = root_directory_offset + root.len() as u64;
+        let leaf_directories_offset = metadata_offset + metadata.len() as u64;
+        let tile_data_offset = leaf_directories_offset + leaves.len() as u64;
+        let header = Header {
+            root_directory_offset,
+            root_directory_length: root.len() as u64,
+            metadata_offset,
+            metadata_length: metadata.len() as u64,
+            leaf_directories_offset,
+            leaf_directories_length: leaves.len() as u64,
+            tile_data_offset,
+            tile_data_length,
+            addressed_tiles_count: self.tiles.len() as u64,
+            tile_entries_count: entries.len() as u64,
+            tile_contents_count: data_order.len() as u64,
+            clustered: true,
+            internal_compression: Compression::Gzip,
+            tile_compression: self.tile_compression,
+            tile_type: self.tile_type,
+            min_zoom,
+            max_zoom,
+            bounds: self.bounds,
+            center_zoom,
+            center,
+        };
+
+        out.write_all(&header.to_bytes())?;
+        out.write_all(&root)?;
+        out.write_all(&metadata)?;
+        out.write_all(&leaves)?;
+        for index in data_order {
+            out.write_all(&self.contents[index])?;
+        }
+        out.flush()?;
+        Ok(header)
+    }
+}
+
+/// Builds the compressed root directory and (concatenated) leaf directories.
+///
+/// All entries go into the root directory if it fits within the first 16 KiB;
+/// otherwise they are split into leaf directories, growing the leaf size until
+/// the root directory fits.
+fn build_directories(entries: &[Entry]) -> Result<(Vec<u8>, Vec<u8>)> {
+    let root = compress(&encode_directory(entries), Compression::Gzip)?;
+    if root.len() <= MAX_ROOT_LEN {
+        return Ok((root, Vec::new()));
+    }
+
+    let mut leaf_size = LEAF_SIZE;
+    loop {
+        let mut leaves = Vec::new();
+        let mut root_entries = Vec::new();
+        for chunk in entries.chunks(leaf_size) {
+            let leaf = compress(&encode_directory(chunk), Compression::Gzip)?;
+            root_entries.push(Entry {
+                tile_id: chunk[0].tile_id,
+                offset: leaves.len() as u64,
+                length: leaf.len() as u32,
+                run_length: 0,
+            });
+            leaves.extend(leaf);
+        }
+        let root = compress(&encode_directory(&root_entries), Compression::Gzip)?;
+        if root.len() <= MAX_ROOT_LEN {
+            return Ok((root, leaves));
+        }
+        leaf_size *= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::pmtiles::PmTilesReader;
+
+    #[test]
+    fn test_roundtrip_with_dedup() {
+        let mut writer = PmTilesWriter::new(TileType::Mvt, Compression::Gzip);
+        writer.set_metadata(r#"{"name":"test"}"#);
+        // added in reverse order; all tiles at z=3 share 2 contents ("land" and "ocean")
+        for x in (0..8).rev() {
+            for y in (0..8).rev() {
+                let data: &[u8] = if x == 3 && y == 4 { b"land" } else { b"ocean" };
+                writer.add_tile((3, x, y), data).unwrap();
+            }
+        }
+        writer.add_tile((0, 0, 0), b"root").unwrap();
+        writer.add_tile((0, 0, 0), b"root (replaced)").unwrap();
+        let mut buf = Vec::new();
+        let header = writer.write(&mut buf).unwrap();
+
+        assert_eq!(header.addressed_tiles_count, 65);
+        assert_eq!(header.tile_contents_count, 3);
+        // z=0, the run of "ocean" before "land", "land", and the run after it
+        assert_eq!(header.tile_entries_count, 4);
+        assert_eq!(header.leaf_directories_length, 0);
+        assert_eq!((header.min_zoom, header.max_zoom), (0, 3));
+
+        let mut reader = PmTilesReader::new(Cursor::new(buf)).unwrap();
+        assert_eq!(reader.header(), &header);
+        assert_eq!(reader.metadata().unwrap(), r#"{"name":"test"}"#);
+        assert_eq!(
+            reader.get_tile((0, 0, 0)).unwrap().as_deref(),
+            Some(&b"root (replaced)"[..])
+        );
+        assert_eq!(
+            reader.get_tile((3, 3, 4)).unwrap().as_deref(),
+            Some(&b"land"[..])
+        );
+        assert_eq!(
+            reader.get_tile((3, 7, 7)).unwrap().as_deref(),
+            Some(&b"ocean"[..])
+        );
+        assert_eq!(reader.get_tile((1, 0, 0)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_leaf_directories() {
+        let mut writer = PmTilesWriter::new(TileType::Mvt, Compression::None);
+        let n: u32 = 1 << 10;
+        for x in 0..n {
+            for y in 0..64u32 {
+                // pseudo-random contents so that entries are neither merged nor contiguous
+                let data = ((x * 7919 + y * 104729) % 997).to_le_bytes();
+                writer.add_tile((10, x, y), &data).unwrap();
+            }
+        }
+        let mut buf = Vec::new();
+        let header = writer.write(&mut buf).unwrap();
+        assert!(header.leaf_directories_length > 0);
+        assert!(header.root_directory_length as usize <= MAX_ROOT_LEN);
+        assert_eq!(header.tile_contents_count, 997);
+
+        let mut reader = PmTilesReader::new(Cursor::new(buf)).unwrap();
+        for (x, y) in [(0u32, 0u32), (1, 63), (500, 31), (1023, 63)] {
+            let expected = ((x * 7919 + y * 104729) % 997).to_le_bytes();
+            assert_eq!(
+                reader.get_tile((10, x, y)).unwrap().as_deref(),
+                Some(&expected[..])
+            );
+        }
+        assert_eq!(reader.get_tile((10, 0, 64)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_invalid_tile() {
+        let mut writer = PmTilesWriter::new(TileType::Mvt, Compression::None);
+        assert!(writer.add_tile((1, 2, 0), b"").is_err());
+    }
+}
@@ -0,0 +1,718 @@
+//! Zero-copy lazy reader for MVT.
+//!
+//! Unlike decoding with [`Tile::decode`](crate::vector_tile::Tile), the views in this module
+//! borrow from the encoded protobuf bytes and only parse what is accessed.
+
+use crate::error::{Error, Result};
+use crate::tag::ValueRef;
+use crate::vector_tile::tile::GeomType;
+
+const WIRE_VARINT: u8 = 0;
+const WIRE_FIXED64: u8 = 1;
+const WIRE_LEN: u8 = 2;
+const WIRE_FIXED32: u8 = 5;
+
+/// Lazy view of an encoded tile.
+#[derive(Debug, Clone, Copy)]
+pub struct TileView<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> TileView<'a> {
+    /// Creates a view of the encoded tile. Nothing is parsed until accessed.
+    #[inline]
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf }
+    }
+
+    /// Returns an iterator over the layers in the tile.
+    pub fn layers(&self) -> Layers<'a> {
+        Layers {
+            reader: ProtoReader::new(self.buf),
+        }
+    }
+
+    /// Finds a layer by name, without parsing the other layers beyond their names.
+    pub fn layer(&self, name: &str) -> Result<Option<LayerView<'a>>> {
+        let mut reader = ProtoReader::new(self.buf);
+        while let Some((field, data)) = reader.next_field()? {
+            if let (3, Data::Bytes(bytes)) = (field, data)
+                && layer_name(bytes)? == name
+            {
+                return LayerView::parse(bytes).map(Some);
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Iterator over the layers of a [`TileView`].
+pub struct Layers<'a> {
+    reader: ProtoReader<'a>,
+}
+
+impl<'a> Iterator for Layers<'a> {
+    type Item = Result<LayerView<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.reader.next_field() {
+                Ok(Some((3, Data::Bytes(bytes)))) => return Some(LayerView::parse(bytes)),
+                Ok(Some(_)) => continue,
+                Ok(None) => return None,
+                Err(e) => {
+                    self.reader.pos = self.reader.buf.len();
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+/// Lazy view of a layer.
+///
+/// The key and value dictionaries are indexed (but not decoded) when the view is created;
+/// features are parsed only while iterating.
+#[derive(Debug, Clone)]
+pub struct LayerView<'a> {
+    buf: &'a [u8],
+    name: &'a str,
+    version: u32,
+    extent: u32,
+    keys: Vec<&'a str>,
+    values: Vec<&'a [u8]>,
+}
+
+impl<'a> LayerView<'a> {
+    fn parse(buf: &'a [u8]) -> Result<Self> {
+        let mut layer = Self {
+            buf,
+            name: "",
+            version: 1,
+            extent: 4096,
+            keys: Vec::new(),
+            values: Vec::new(),
+        };
+        let mut reader = ProtoReader::new(buf);
+        while let Some((field, data)) = reader.next_field()? {
+            match (field, data) {
+                (1, Data::Bytes(bytes)) => layer.name = to_str(bytes)?,
+                (3, Data::Bytes(bytes)) => layer.keys.push(to_str(bytes)?),
+                (4, Data::Bytes(bytes)) => layer.values.push(bytes),
+                (5, Data::Varint(v)) => layer.extent = v as u32,
+                (15, Data::Varint(v)) => layer.version = v as u32,
+                _ => {}
+            }
+        }
+        Ok(layer)
+    }
+
+    /// Name of the layer.
+    #[inline]
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// Version of the layer (defaults to 1 when absent).
+    #[inline]
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Extent of the layer (defaults to 4096 when absent).
+    #[inline]
+    pub fn extent(&self) -> u32 {
+        self.extent
+    }
+
+    /// Keys dictionary of the layer.
+    #[inline]
+    pub fn keys(&self) -> &[&'a str] {
+        &self.keys
+    }
+
+    /// Number of entries in the values dictionary.
+    #[inline]
+    pub fn values_len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns the key at the given index.
+    #[inline]
+    pub fn key(&self, idx: usize) -> Option<&'a str> {
+        self.keys.get(idx).copied()
+    }
+
+    /// Decodes the value at the given index. Strings are borrowed from the encoded tile.
+    pub fn value(&self, idx: usize) -> Result<ValueRef<'a>> {
+        let bytes = self
+            .values
+            .get(idx)
+            .ok_or(Error::ValueIndexOutOfBounds { index: idx })?;
+        parse_value(bytes)?.ok_or(Error::InvalidValue { index: idx })
+    }
+
+    /// Returns an iterator over the features in the layer.
+    pub fn features(&self) -> Features<'a> {
+        Features {
+            reader: ProtoReader::new(self.buf),
+        }
+    }
+}
+
+/// Iterator over the features of a [`LayerView`].
+pub struct Features<'a> {
+    reader: ProtoReader<'a>,
+}
+
+impl<'a> Iterator for Features<'a> {
+    type Item = Result<FeatureView<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.reader.next_field() {
+                Ok(Some((2, Data::Bytes(bytes)))) => return Some(FeatureView::parse(bytes)),
+                Ok(Some(_)) => continue,
+                Ok(None) => return None,
+                Err(e) => {
+                    self.reader.pos = self.reader.buf.len();
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+/// Lazy view of a feature.
+#[derive(Debug, Clone, Copy)]
+pub struct FeatureView<'a> {
+    buf: &'a [u8],
+    id: Option<u64>,
+    geom_type: Option<i32>,
+    tags: Repeated<'a>,
+    geometry: Repeated<'a>,
+}
+
+/// Location of a repeated `uint32` field within a feature message.
+#[derive(Debug, Clone, Copy)]
+enum Repeated<'a> {
+    /// The field is absent or stored as a single packed chunk.
+    Packed(&'a [u8]),
+    /// The field is unpacked or split into several chunks, and is read from the whole message.
+    Scattered,
+}
+
+impl<'a> Repeated<'a> {
+    /// Records an occurrence of the field.
+    fn add(&mut self, data: Data<'a>, offset: usize) -> Result<()> {
+        match data {
+            Data::Bytes(bytes) => {
+                check_packed(bytes)?;
+                *self = match *self {
+                    Repeated::Packed([]) => Repeated::Packed(bytes),
+                    _ => Repeated::Scattered,
+                };
+            }
+            Data::Varint(_) => *self = Repeated::Scattered,
+            Data::Fixed64(_) => {
+                return Err(Error::InvalidWireType {
+                    wire_type: WIRE_FIXED64,
+                    offset,
+                });
+            }
+            Data::Fixed32(_) => {
+                return Err(Error::InvalidWireType {
+                    wire_type: WIRE_FIXED32,
+                    offset,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> FeatureView<'a> {
+    fn parse(buf: &'a [u8]) -> Result<Self> {
+        let mut feature = Self {
+            buf,
+            id: None,
+            geom_type: None,
+            tags: Repeated::Packed(&[]),
+            geometry: Repeated::Packed(&[]),
+        };
+        let mut reader = ProtoReader::new(buf);
+        while let Some((field, data)) = reader.next_field()? {
+            match (field, data) {
+                (1, Data::Varint(v)) => feature.id = Some(v),
+                (2, data) => feature.tags.add(data, reader.pos)?,
+                (3, Data::Varint(v)) => feature.geom_type = Some(v as i32),
+                (4, data) => feature.geometry.add(data, reader.pos)?,
+                _ => {}
+            }
+        }
+        Ok(feature)
+    }
+
+    fn repeated(&self, field: u32, repeated: Repeated<'a>) -> PackedIter<'a> {
+        match repeated {
+            Repeated::Packed(buf) => PackedIter { buf, fields: None },
+            Repeated::Scattered => PackedIter {
+                buf: &[],
+                fields: Some((ProtoReader::new(self.buf), field)),
+            },
+        }
+    }
+
+    /// ID of the feature, if set.
+    #[inline]
+    pub fn id(&self) -> Option<u64> {
+        self.id
+    }
+
+    /// Geometry type of the feature. Missing or unrecognized types are reported as `Unknown`.
+    #[inline]
+    pub fn geom_type(&self) -> GeomType {
+        self.geom_type
+            .and_then(|t| GeomType::try_from(t).ok())
+            .unwrap_or(GeomType::Unknown)
+    }
+
+    /// Raw tag indices (alternating key and value indices).
+    #[inline]
+    pub fn tags(&self) -> PackedIter<'a> {
+        self.repeated(2, self.tags)
+    }
+
+    /// Raw geometry commands and parameters.
+    #[inline]
+    pub fn geometry(&self) -> PackedIter<'a> {
+        self.repeated(4, self.geometry)
+    }
+
+    /// Returns an iterator over the decoded key-value pairs of the feature.
+    pub fn properties<'l>(&self, layer: &'l LayerView<'a>) -> Properties<'a, 'l> {
+        Properties {
+            layer,
+            tags: self.tags(),
+            consumed: 0,
+        }
+    }
+}
+
+/// Iterator over the key-value pairs of a [`FeatureView`].
+pub struct Properties<'a, 'l> {
+    layer: &'l LayerView<'a>,
+    tags: PackedIter<'a>,
+    consumed: usize,
+}
+
+impl<'a> Iterator for Properties<'a, '_> {
+    type Item = Result<(&'a str, ValueRef<'a>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key_idx = self.tags.next()? as usize;
+        let Some(value_idx) = self.tags.next() else {
+            return Some(Err(Error::OddTagsLength {
+                len: self.consumed + 1,
+            }));
+        };
+        self.consumed += 2;
+        let Some(key) = self.layer.key(key_idx) else {
+            return Some(Err(Error::KeyIndexOutOfBounds { index: key_idx }));
+        };
+        Some(
+            self.layer
+                .value(value_idx as usize)
+                .map(|value| (key, value)),
+        )
+    }
+}
+
+/// Iterator over a repeated `uint32` field, either packed or unpacked.
+///
+/// Varints wider than 32 bits are truncated, as protobuf does for `uint32` fields.
+#[derive(Debug, Clone)]
+pub struct PackedIter<'a> {
+    buf: &'a [u8],
+    /// Remaining fields of the message and the field number, if the field is not a single packed chunk.
+    fields: Option<(ProtoReader<'a>, u32)>,
+}
+
+impl PackedIter<'_> {
+    #[inline]
+    fn next_packed(&mut self) -> Option<u32> {
+        let mut value = 0u64;
+        for (i, &b) in self.buf.iter().enumerate() {
+            if i < 10 {
+                value |= ((b & 0x7f) as u64) << (7 * i);
+            }
+            if b & 0x80 == 0 {
+                self.buf = &self.buf[i + 1..];
+                return Some(value as u32);
+            }
+        }
+        None
+    }
+}
+
+impl Iterator for PackedIter<'_> {
+    type Item = u32;
+
+    #[inline]
+    fn next(&mut self) -> Option<u32> {
+        loop {
+            if let Some(value) = self.next_packed() {
+                return Some(value);
+            }
+            let (reader, field) = self.fields.as_mut()?;
+            // the message has already been parsed successfully, so errors cannot occur here
+            match reader.next_field() {
+                Ok(Some((f, Data::Bytes(bytes)))) if f == *field => self.buf = bytes,
+                Ok(Some((f, Data::Varint(v)))) if f == *field => return Some(v as u32),
+                Ok(Some(_)) => {}
+                Ok(None) | Err(_) => {
+                    self.fields = None;
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+/// Checks that a packed varint buffer is not truncated.
+///
+/// If the last byte terminates a varint, every varint in the buffer is terminated.
+#[inline]
+fn check_packed(bytes: &[u8]) -> Result<&[u8]> {
+    match bytes.last() {
+        Some(b) if b & 0x80 != 0 => Err(Error::Truncated {
+            offset: bytes.len(),
+        }),
+        _ => Ok(bytes),
+    }
+}
+
+/// Scans a layer message for its name only.
+fn layer_name(buf: &[u8]) -> Result<&str> {
+    let mut reader = ProtoReader::new(buf);
+    while let Some((field, data)) = reader.next_field()? {
+        if let (1, Data::Bytes(bytes)) = (field, data) {
+            return to_str(bytes);
+        }
+    }
+    Ok("")
+}
+
+/// Parses a `Value` message, with the same precedence as [`ValueRef::from_tile_value`].
+fn parse_value(buf: &[u8]) -> Result<Option<ValueRef<'_>>> {
+    let mut string_value = None;
+    let mut float_value = None;
+    let mut double_value = None;
+    let mut int_value = None;
+    let mut uint_value = None;
+    let mut sint_value = None;
+    let mut bool_value = None;
+
+    let mut reader = ProtoReader::new(buf);
+    while let Some((field, data)) = reader.next_field()? {
+        match (field, data) {
+            (1, Data::Bytes(bytes)) => string_value = Some(to_str(bytes)?),
+            (2, Data::Fixed32(v)) => float_value = Some(f32::from_bits(v)),
+            (3, Data::Fixed64(v)) => double_value = Some(f64::from_bits(v)),
+            (4, Data::Varint(v)) => int_value = Some(v as i64),
+            (5, Data::Varint(v)) => uint_value = Some(v),
+            (6, Data::Varint(v)) => sint_value = Some(((v >> 1) as i64) ^ -((v & 1) as i64)),
+            (7, Data::Varint(v)) => bool_value = Some(v != 0),
+            _ => {}
+        }
+    }
+
+    let value = if let Some(s) = string_value {
+        ValueRef::String(s)
+    } else if let Some(f) = float_value {
+        ValueRef::Float(f.to_ne_bytes())
+    } else if let Some(d) = double_value {
+        ValueRef::Double(d.to_ne_bytes())
+    } else if let Some(i) = int_value {
+        ValueRef::Int(i)
+    } else if let Some(u) = uint_value {
+        ValueRef::Uint(u)
+    } else if let Some(s) = sint_value {
+        ValueRef::SInt(s)
+    } else if let Some(b) = bool_value {
+        ValueRef::Bool(b)
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(value))
+}
+
+#[inline]
+fn to_str(bytes: &[u8]) -> Result<&str> {
+    std::str::from_utf8(bytes).map_err(|e| Error::InvalidUtf8 {
+        offset: e.valid_up_to(),
+    })
+}
+
+/// Payload of a protobuf field.
+#[derive(Debug, Clone, Copy)]
+enum Data<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Bytes(&'a [u8]),
+    Fixed32(u32),
+}
+
+/// Minimal protobuf wire-format reader.
+#[derive(Debug, Clone)]
+struct ProtoReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ProtoReader<'a> {
+    #[inline]
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    /// Reads the next field as (field number, payload).
+    fn next_field(&mut self) -> Result<Option<(u32, Data<'a>)>> {
+        if self.pos >= self.buf.len() {
+            return Ok(None);
+        }
+        let key = self.read_varint()?;
+        let field = (key >> 3) as u32;
+        let data = match (key & 0x7) as u8 {
+            WIRE_VARINT => Data::Varint(self.read_varint()?),
+            WIRE_FIXED64 => Data::Fixed64(u64::from_le_bytes(self.read_array()?)),
+            WIRE_LEN => {
+                let len = self.read_varint()? as usize;
+                Data::Bytes(self.read_bytes(len)?)
+            }
+            WIRE_FIXED32 => Data::Fixed32(u32::from_le_bytes(self.read_array()?)),
+            wire_type => {
+                return Err(Error::InvalidWireType {
+                    wire_type,
+                    offset: self.pos,
+                });
+            }
+        };
+        Ok(Some((field, data)))
+    }
+
+    fn read_varint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for i in 0..10 {
+            let Some(&b) = self.buf.get(self.pos) else {
+                break;
+            };
+            self.pos += 1;
+            value |= ((b & 0x7f) as u64) << (7 * i);
+            if b & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        if self.pos >= self.buf.len() {
+            Err(Error::Truncated { offset: self.pos })
+        } else {
+            Err(Error::InvalidVarint { offset: self.pos })
+        }
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.pos.saturating_add(len);
+        if end > self.buf.len() {
+            return Err(Error::Truncated { offset: self.pos });
+        }
+        let bytes = &self.buf[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.read_bytes(N)?.try_into().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prost::Message;
+
+    use super::*;
+    use crate::geometry::GeometryDecoder;
+    use crate::tag::TagsDecoder;
+    use crate::vector_tile::{Tile, tile};
+
+    fn make_tile() -> Tile {
+        let layer = |name: &str, n: u64| tile::Layer {
+            version: 2,
+            name: name.to_string(),
+            features: (0..n)
+                .map(|i| tile::Feature {
+                    id: Some(i),
+                    tags: vec![0, 0, 1, 1],
+                    r#type: Some(tile::GeomType::Point as i32),
+                    geometry: vec![9, 2 * i as u32, 300],
+                })
+                .collect(),
+            keys: vec!["name".to_string(), "rank".to_string()],
+            values: vec![
+                tile::Value {
+                    string_value: Some(name.to_string()),
+                    ..Default::default()
+                },
+                tile::Value {
+                    sint_value: Some(-3),
+                    ..Default::default()
+                },
+            ],
+            extent: Some(512),
+        };
+        Tile {
+            layers: vec![layer("roads", 2), layer("water", 3)],
+        }
+    }
+
+    #[test]
+    fn test_tile_view_layers() {
+        let tile = make_tile();
+        let buf = tile.encode_to_vec();
+        let view = TileView::new(&buf);
+
+        let layers: Vec<_> = view.layers().collect::<Result<_>>().unwrap();
+        assert_eq!(layers.len(), 2);
+        for (layer, expected) in layers.iter().zip(&tile.layers) {
+            assert_eq!(layer.name(), expected.name);
+            assert_eq!(layer.version(), 2);
+            assert_eq!(layer.extent(), 512);
+            assert_eq!(layer.keys(), ["name", "rank"]);
+            assert_eq!(layer.values_len(), 2);
+
+            let features: Vec<_> = layer.features().collect::<Result<_>>().unwrap();
+            assert_eq!(features.len(), expected.features.len());
+            for (feature, expected) in features.iter().zip(&expected.features) {
+                assert_eq!(feature.id(), expected.id);
+                assert_eq!(feature.geom_type(), tile::GeomType::Point);
+                assert_eq!(feature.tags().collect::<Vec<_>>(), expected.tags);
+                assert_eq!(feature.geometry().collect::<Vec<_>>(), expected.geometry);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tile_view_layer_by_name() {
+        let tile = make_tile();
+        let buf = tile.encode_to_vec();
+        let view = TileView::new(&buf);
+
+        let layer = view.layer("water").unwrap().unwrap();
+        assert_eq!(layer.name(), "water");
+        assert_eq!(layer.features().count(), 3);
+        assert!(view.layer("buildings").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_feature_view_decode() {
+        let tile = make_tile();
+        let buf = tile.encode_to_vec();
+        let layer = TileView::new(&buf).layer("roads").unwrap().unwrap();
+        let feature = layer.features().nth(1).unwrap().unwrap();
+
+        let properties: Vec<_> = feature.properties(&layer).collect::<Result<_>>().unwrap();
+        let expected = &tile.layers[0];
+        let decoder = TagsDecoder::new(&expected.keys, &expected.values);
+        let expected_properties: Vec<_> = decoder
+            .iter(&expected.features[1].tags)
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(properties, expected_properties);
+        // strings are borrowed from the encoded tile
+        let ValueRef::String(name) = properties[0].1 else {
+            panic!("expected a string");
+        };
+        assert!(buf.as_ptr_range().contains(&name.as_ptr()));
+
+        let geometry: Vec<u32> = feature.geometry().collect();
+        let points = GeometryDecoder::new(&geometry).decode_points().unwrap();
+        assert_eq!(points, [[1, 150]]);
+    }
+
+    #[test]
+    fn test_default_fields() {
+        let tile = Tile {
+            layers: vec![tile::Layer {
+                version: 1,
+                name: "empty".to_string(),
+                features: vec![tile::Feature::default()],
+                keys: vec![],
+                values: vec![],
+                extent: None,
+            }],
+        };
+        let buf = tile.encode_to_vec();
+        let layer = TileView::new(&buf).layer("empty").unwrap().unwrap();
+        assert_eq!(layer.version(), 1);
+        assert_eq!(layer.extent(), 4096);
+        let feature = layer.features().next().unwrap().unwrap();
+        assert_eq!(feature.id(), None);
+        assert_eq!(feature.geom_type(), tile::GeomType::Unknown);
+        assert_eq!(feature.geometry().count(), 0);
+        assert!(layer.value(0).is_err());
+    }
+
+    #[test]
+    fn test_malformed() {
+        // truncated layer message
+        let buf = [0x1a, 0x05, 0x0a, 0x01];
+        let view = TileView::new(&buf);
+        assert!(view.layers().next().unwrap().is_err());
+        assert!(view.layer("x").is_err());
+
+        // truncated packed geometry
+        let buf = [0x22, 0x02, 0x09, 0x80];
+        assert_eq!(
+            FeatureView::parse(&buf).unwrap_err(),
+            Error::Truncated { offset: 2 }
+        );
+
+        // geometry with a fixed32 wire type
+        let buf = [0x25, 0x09, 0x00, 0x00, 0x00];
+        assert_eq!(
+            FeatureView::parse(&buf).unwrap_err(),
+            Error::InvalidWireType {
+                wire_type: WIRE_FIXED32,
+                offset: 5
+            }
+        );
+    }
+
+    #[test]
+    fn test_unpacked_repeated_fields() {
+        // unpacked tags, geometry split into packed and unpacked parts, type in between
+        let buf = [
+            0x10, 0x00, 0x22, 0x01, 0x09, 0x18, 0x01, 0x10, 0x01, 0x20, 0x02, 0x22, 0x01, 0x04,
+        ];
+        let expected = tile::Feature::decode(&buf[..]).unwrap();
+        assert_eq!(expected.tags, [0, 1]);
+        assert_eq!(expected.geometry, [9, 2, 4]);
+
+        let feature = FeatureView::parse(&buf).unwrap();
+        assert_eq!(feature.geom_type(), tile::GeomType::Point);
+        assert_eq!(feature.tags().collect::<Vec<_>>(), expected.tags);
+        assert_eq!(feature.geometry().collect::<Vec<_>>(), expected.geometry);
+
+        // a single unpacked value
+        let feature = FeatureView::parse(&[0x20, 0x09]).unwrap();
+        assert_eq!(feature.geometry().collect::<Vec<_>>(), [9]);
+    }
+
+    #[test]
+    fn test_packed_iter() {
+        let iter = PackedIter {
+            buf: &[0x00, 0x96, 0x01, 0xff, 0xff, 0xff, 0xff, 0x0f],
+            fields: None,
+        };
+        assert_eq!(iter.collect::<Vec<_>>(), [0, 150, u32::MAX]);
+    }
+}
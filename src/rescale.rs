@@ -0,0 +1,138 @@
+//! Rescaling layers from one extent to another.
+
+use crate::error::Result;
+use crate::geometry::{GeometryEncoder, RingClassification, transform_geometry};
+use crate::vector_tile::tile::{Feature, GeomType, Layer};
+
+/// Rescales a layer to the given extent and updates `Layer.extent`.
+///
+/// Coordinates are scaled and rounded to the nearest integer. Segments that collapse to zero
+/// length are removed, line strings that collapse to a single point and rings that collapse to
+/// zero area are dropped, and features left without geometry are dropped. Ring winding order is
+/// preserved. Keys and values are kept as they are.
+pub fn rescale_layer(layer: &Layer, extent: u32) -> Result<Layer> {
+    let from = layer.extent.unwrap_or(4096);
+    let mut features = Vec::with_capacity(layer.features.len());
+    for feature in &layer.features {
+        let geometry = if from == extent {
+            feature.geometry.clone()
+        } else {
+            let rings = RingClassification::for_version(layer.version);
+            rescale_geometry(feature.r#type(), &feature.geometry, rings, from, extent)?
+        };
+        if geometry.is_empty() {
+            continue;
+        }
+        features.push(Feature {
+            geometry,
+            ..feature.clone()
+        });
+    }
+    Ok(Layer {
+        features,
+        extent: Some(extent),
+        ..layer.clone()
+    })
+}
+
+/// Rescales an encoded geometry from one extent to another.
+pub(crate) fn rescale_geometry(
+    geom_type: GeomType,
+    geometry: &[u32],
+    ring_classification: RingClassification,
+    from: u32,
+    to: u32,
+) -> Result<Vec<u32>> {
+    let ratio = to as f64 / from as f64;
+    let scale = |v: i32| (v as f64 * ratio).round() as i32;
+    transform_geometry(
+        geom_type,
+        geometry,
+        ring_classification,
+        None,
+        |[x, y]| [scale(x), scale(y)],
+        GeometryEncoder::new(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{GeometryDecoder, calculate_signed_area};
+
+    fn make_layer(extent: u32, features: Vec<(GeomType, Vec<u32>)>) -> Layer {
+        Layer {
+            version: 2,
+            name: "test".to_string(),
+            features: features
+                .into_iter()
+                .map(|(geom_type, geometry)| Feature {
+                    id: None,
+                    tags: vec![],
+                    r#type: Some(geom_type as i32),
+                    geometry,
+                })
+                .collect(),
+            keys: vec![],
+            values: vec![],
+            extent: Some(extent),
+        }
+    }
+
+    #[test]
+    fn test_rescale_up_and_down() {
+        let mut geom_enc = GeometryEncoder::new();
+        geom_enc.add_linestring([[0, 0], [100, 50], [512, 512]]);
+        let layer = make_layer(512, vec![(GeomType::Linestring, geom_enc.into_vec())]);
+
+        let rescaled = rescale_layer(&layer, 4096).unwrap();
+        assert_eq!(rescaled.extent, Some(4096));
+        let lines = GeometryDecoder::new(&rescaled.features[0].geometry)
+            .decode_linestrings()
+            .unwrap();
+        assert_eq!(lines, [vec![[0, 0], [800, 400], [4096, 4096]]]);
+
+        let back = rescale_layer(&rescaled, 512).unwrap();
+        assert_eq!(back, layer);
+    }
+
+    #[test]
+    fn test_rescale_drops_collapsed_geometries() {
+        // a line and a ring that collapse when scaling 8192 -> 512 (factor 1/16)
+        let mut line_enc = GeometryEncoder::new();
+        line_enc.add_linestring([[0, 0], [5, 3], [7, 0]]);
+        line_enc.add_linestring([[0, 0], [80, 0], [84, 2], [160, 0]]);
+        let mut polygon_enc = GeometryEncoder::new();
+        polygon_enc.add_polygon([
+            vec![[0, 0], [1600, 0], [1600, 1600], [0, 1600]],
+            vec![[100, 100], [100, 103], [103, 103], [103, 100]],
+        ]);
+        let mut tiny_enc = GeometryEncoder::new();
+        tiny_enc.add_polygon([[[0, 0], [7, 0], [7, 7], [0, 7]]]);
+        let layer = make_layer(
+            8192,
+            vec![
+                (GeomType::Linestring, line_enc.into_vec()),
+                (GeomType::Polygon, polygon_enc.into_vec()),
+                (GeomType::Polygon, tiny_enc.into_vec()),
+            ],
+        );
+
+        let rescaled = rescale_layer(&layer, 512).unwrap();
+        assert_eq!(rescaled.features.len(), 2);
+
+        // the first line collapsed; in the second one, the collapsed segment is removed
+        let lines = GeometryDecoder::new(&rescaled.features[0].geometry)
+            .decode_linestrings()
+            .unwrap();
+        assert_eq!(lines, [vec![[0, 0], [5, 0], [10, 0]]]);
+
+        // the hole collapsed
+        let polygons = GeometryDecoder::new(&rescaled.features[1].geometry)
+            .decode_polygons()
+            .unwrap();
+        assert_eq!(polygons.len(), 1);
+        assert_eq!(polygons[0].len(), 1);
+        assert_eq!(calculate_signed_area(&polygons[0][0]), 100.0 * 100.0);
+    }
+}
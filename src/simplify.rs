@@ -0,0 +1,337 @@
+//! Line and ring simplification in tile coordinates.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::geometry::calculate_signed_area;
+
+/// Tolerance-based simplification algorithm.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Simplifier {
+    /// Douglas–Peucker, with the maximum allowed distance from the original line (in tile units).
+    DouglasPeucker(f64),
+    /// Visvalingam–Whyatt, with the minimum effective triangle area to keep (in squared tile units).
+    Visvalingam(f64),
+}
+
+impl Simplifier {
+    /// Simplifies a line string. The first and last vertices are always kept.
+    pub fn simplify_linestring(&self, line: &[[i32; 2]]) -> Vec<[i32; 2]> {
+        if line.len() < 3 {
+            return line.to_vec();
+        }
+        let keep = match *self {
+            Simplifier::DouglasPeucker(tolerance) => douglas_peucker(line, tolerance, &[]),
+            Simplifier::Visvalingam(min_area) => visvalingam(line, min_area, false),
+        };
+        select(line, &keep)
+    }
+
+    /// Simplifies a polygon ring.
+    ///
+    /// The ring may be given either open or closed; the result is open, as expected by
+    /// [`GeometryEncoder::add_ring`](crate::geometry::GeometryEncoder::add_ring).
+    /// The simplified ring always keeps at least three distinct vertices (four including
+    /// the implicit closing vertex) and never flips its winding order. If simplification
+    /// would violate either guarantee, the original ring is returned.
+    pub fn simplify_ring(&self, ring: &[[i32; 2]]) -> Vec<[i32; 2]> {
+        let ring = match ring {
+            [first, .., last] if first == last => &ring[..ring.len() - 1],
+            _ => ring,
+        };
+        if ring.len() <= 3 {
+            return ring.to_vec();
+        }
+
+        let simplified = match *self {
+            Simplifier::DouglasPeucker(tolerance) => {
+                // Split the ring at the vertex farthest from the first one, so that both
+                // halves are simplified as open paths with fixed endpoints.
+                let far = (1..ring.len())
+                    .max_by_key(|&i| dist_sq_i64(ring[0], ring[i]))
+                    .unwrap();
+                let mut closed = ring.to_vec();
+                closed.push(ring[0]);
+                let mut keep = douglas_peucker(&closed, tolerance, &[far]);
+                keep.pop();
+
+                if keep.iter().filter(|&&k| k).count() < 3 {
+                    // keep the vertex farthest from the chord
+                    let third = (1..ring.len())
+                        .filter(|&i| i != far)
+                        .max_by(|&i, &j| {
+                            seg_dist_sq(ring[i], ring[0], ring[far])
+                                .total_cmp(&seg_dist_sq(ring[j], ring[0], ring[far]))
+                        })
+                        .unwrap();
+                    keep[third] = true;
+                }
+                select(ring, &keep)
+            }
+            Simplifier::Visvalingam(min_area) => select(ring, &visvalingam(ring, min_area, true)),
+        };
+
+        let original_area = calculate_signed_area(ring);
+        let simplified_area = calculate_signed_area(&simplified);
+        if simplified.len() < 3
+            || simplified_area == 0.0
+            || original_area.signum() != simplified_area.signum()
+        {
+            return ring.to_vec();
+        }
+        simplified
+    }
+}
+
+#[inline]
+fn select(points: &[[i32; 2]], keep: &[bool]) -> Vec<[i32; 2]> {
+    points
+        .iter()
+        .zip(keep)
+        .filter_map(|(&p, &k)| k.then_some(p))
+        .collect()
+}
+
+/// Marks the vertices kept by the Douglas–Peucker algorithm.
+///
+/// The endpoints and the vertices in `fixed` are always kept.
+fn douglas_peucker(points: &[[i32; 2]], tolerance: f64, fixed: &[usize]) -> Vec<bool> {
+    let mut keep = vec![false; points.len()];
+    let last = points.len() - 1;
+    keep[0] = true;
+    keep[last] = true;
+
+    let mut anchors = vec![0];
+    anchors.extend(fixed.iter().copied().filter(|&i| i > 0 && i < last));
+    anchors.push(last);
+    anchors.sort_unstable();
+    anchors.dedup();
+
+    let tolerance_sq = tolerance * tolerance;
+    let mut stack: Vec<(usize, usize)> = anchors.windows(2).map(|w| (w[0], w[1])).collect();
+    while let Some((first, last)) = stack.pop() {
+        keep[first] = true;
+        keep[last] = true;
+        if last - first < 2 {
+            continue;
+        }
+        let (mut max_dist, mut max_idx) = (0.0, first);
+        for (i, &p) in points.iter().enumerate().take(last).skip(first + 1) {
+            let d = seg_dist_sq(p, points[first], points[last]);
+            if d > max_dist {
+                (max_dist, max_idx) = (d, i);
+            }
+        }
+        if max_dist > tolerance_sq {
+            stack.push((first, max_idx));
+            stack.push((max_idx, last));
+        }
+    }
+    keep
+}
+
+/// Marks the vertices kept by the Visvalingam–Whyatt algorithm.
+///
+/// For open lines the endpoints are always kept; for rings at least three vertices are kept.
+fn visvalingam(points: &[[i32; 2]], min_area: f64, is_ring: bool) -> Vec<bool> {
+    let n = points.len();
+    let mut keep = vec![true; n];
+    let mut prev: Vec<usize> = (0..n).map(|i| (i + n - 1) % n).collect();
+    let mut next: Vec<usize> = (0..n).map(|i| (i + 1) % n).collect();
+    let mut generation = vec![0u32; n];
+
+    let removable = |i: usize| is_ring || (i != 0 && i != n - 1);
+    let area = |p: usize, i: usize, q: usize| triangle_area(points[p], points[i], points[q]);
+
+    let mut heap = BinaryHeap::new();
+    for i in (0..n).filter(|&i| removable(i)) {
+        heap.push(Candidate {
+            area: area(prev[i], i, next[i]),
+            index: i,
+            generation: 0,
+        });
+    }
+
+    let mut remaining = n;
+    let min_remaining = if is_ring { 3 } else { 2 };
+    let mut max_area = 0.0f64;
+    while let Some(Candidate {
+        area: a,
+        index: i,
+        generation: g,
+    }) = heap.pop()
+    {
+        if !keep[i] || g != generation[i] {
+            continue;
+        }
+        // effective areas never decrease, so removal order stays consistent
+        max_area = max_area.max(a);
+        if max_area >= min_area || remaining <= min_remaining {
+            break;
+        }
+        keep[i] = false;
+        remaining -= 1;
+
+        let (p, q) = (prev[i], next[i]);
+        next[p] = q;
+        prev[q] = p;
+        for j in [p, q] {
+            if removable(j) {
+                generation[j] += 1;
+                heap.push(Candidate {
+                    area: area(prev[j], j, next[j]),
+                    index: j,
+                    generation: generation[j],
+                });
+            }
+        }
+    }
+    keep
+}
+
+/// Heap entry for Visvalingam–Whyatt, ordered so that the smallest area is popped first.
+struct Candidate {
+    area: f64,
+    index: usize,
+    generation: u32,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .area
+            .total_cmp(&self.area)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+#[inline]
+fn triangle_area(a: [i32; 2], b: [i32; 2], c: [i32; 2]) -> f64 {
+    let cross = (b[0] as i64 - a[0] as i64) * (c[1] as i64 - a[1] as i64)
+        - (c[0] as i64 - a[0] as i64) * (b[1] as i64 - a[1] as i64);
+    cross.abs() as f64 / 2.0
+}
+
+#[inline]
+fn dist_sq_i64(a: [i32; 2], b: [i32; 2]) -> i64 {
+    let dx = a[0] as i64 - b[0] as i64;
+    let dy = a[1] as i64 - b[1] as i64;
+    dx * dx + dy * dy
+}
+
+/// Squared distance from `p` to the segment `a`-`b`.
+#[inline]
+fn seg_dist_sq(p: [i32; 2], a: [i32; 2], b: [i32; 2]) -> f64 {
+    let (px, py) = (p[0] as f64, p[1] as f64);
+    let (ax, ay) = (a[0] as f64, a[1] as f64);
+    let (dx, dy) = (b[0] as f64 - ax, b[1] as f64 - ay);
+    let len_sq = dx * dx + dy * dy;
+    let t = if len_sq > 0.0 {
+        (((px - ax) * dx + (py - ay) * dy) / len_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let (ex, ey) = (ax + t * dx - px, ay + t * dy - py);
+    ex * ex + ey * ey
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_douglas_peucker_linestring() {
+        let line = [[0, 0], [10, 1], [20, 0], [30, 10], [40, 0]];
+        let simplified = Simplifier::DouglasPeucker(2.0).simplify_linestring(&line);
+        assert_eq!(simplified, [[0, 0], [20, 0], [30, 10], [40, 0]]);
+
+        let simplified = Simplifier::DouglasPeucker(20.0).simplify_linestring(&line);
+        assert_eq!(simplified, [[0, 0], [40, 0]]);
+
+        let simplified = Simplifier::DouglasPeucker(0.0).simplify_linestring(&line);
+        assert_eq!(simplified, line);
+    }
+
+    #[test]
+    fn test_visvalingam_linestring() {
+        let line = [[0, 0], [10, 1], [20, 0], [30, 10], [40, 0]];
+        let simplified = Simplifier::Visvalingam(20.0).simplify_linestring(&line);
+        assert_eq!(simplified, [[0, 0], [20, 0], [30, 10], [40, 0]]);
+
+        let simplified = Simplifier::Visvalingam(1000.0).simplify_linestring(&line);
+        assert_eq!(simplified, [[0, 0], [40, 0]]);
+    }
+
+    #[test]
+    fn test_short_linestring() {
+        let line = [[0, 0], [10, 10]];
+        for simplifier in [
+            Simplifier::DouglasPeucker(100.0),
+            Simplifier::Visvalingam(100.0),
+        ] {
+            assert_eq!(simplifier.simplify_linestring(&line), line);
+        }
+    }
+
+    #[test]
+    fn test_simplify_ring_keeps_minimum_vertices() {
+        let ring = [
+            [0, 0],
+            [50, 1],
+            [100, 0],
+            [101, 50],
+            [100, 100],
+            [50, 99],
+            [0, 100],
+            [1, 50],
+        ];
+        for simplifier in [
+            Simplifier::DouglasPeucker(5.0),
+            Simplifier::DouglasPeucker(1000.0),
+            Simplifier::Visvalingam(100.0),
+            Simplifier::Visvalingam(1e9),
+        ] {
+            let simplified = simplifier.simplify_ring(&ring);
+            assert!(simplified.len() >= 3, "{simplifier:?}");
+            assert!(calculate_signed_area(&simplified) > 0.0, "{simplifier:?}");
+        }
+
+        let simplified = Simplifier::DouglasPeucker(5.0).simplify_ring(&ring);
+        assert_eq!(simplified, [[0, 0], [100, 0], [100, 100], [0, 100]]);
+    }
+
+    #[test]
+    fn test_simplify_ring_keeps_winding() {
+        // counter-clockwise (negative area) hole
+        let ring = [[0, 0], [0, 100], [50, 101], [100, 100], [100, 0], [50, -1]];
+        for simplifier in [
+            Simplifier::DouglasPeucker(5.0),
+            Simplifier::Visvalingam(1e9),
+        ] {
+            let simplified = simplifier.simplify_ring(&ring);
+            assert!(simplified.len() >= 3);
+            assert!(calculate_signed_area(&simplified) < 0.0);
+        }
+    }
+
+    #[test]
+    fn test_simplify_closed_ring() {
+        let ring = [[0, 0], [100, 0], [100, 100], [50, 100], [0, 100], [0, 0]];
+        let simplified = Simplifier::DouglasPeucker(1.0).simplify_ring(&ring);
+        assert_eq!(simplified, [[0, 0], [100, 0], [100, 100], [0, 100]]);
+    }
+}
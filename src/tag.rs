@@ -3,6 +3,7 @@
 use foldhash::fast::RandomState;
 use indexmap::IndexSet;
 
+use crate::error::{Error, Result};
 use crate::vector_tile::tile;
 
 #[derive(Default)]
@@ -160,28 +161,74 @@ impl From<bool> for Value {
 
 impl Value {
     /// Creates a Value from a tile::Value.
-    #[allow(clippy::manual_map)]
     pub fn from_tile_value(v: &tile::Value) -> Option<Self> {
+        ValueRef::from_tile_value(v).map(Value::from)
+    }
+
+    /// Borrows the value as a [`ValueRef`].
+    pub fn as_value_ref(&self) -> ValueRef<'_> {
+        match *self {
+            Value::String(ref v) => ValueRef::String(v),
+            Value::Float(v) => ValueRef::Float(v),
+            Value::Double(v) => ValueRef::Double(v),
+            Value::Int(v) => ValueRef::Int(v),
+            Value::Uint(v) => ValueRef::Uint(v),
+            Value::SInt(v) => ValueRef::SInt(v),
+            Value::Bool(v) => ValueRef::Bool(v),
+        }
+    }
+}
+
+/// Borrowed counterpart of [`Value`], referring to a string in a decoded layer or an encoded tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueRef<'a> {
+    String(&'a str),
+    Float([u8; 4]),
+    Double([u8; 8]),
+    Int(i64),
+    Uint(u64),
+    SInt(i64),
+    Bool(bool),
+}
+
+impl<'a> ValueRef<'a> {
+    /// Creates a ValueRef borrowing from a tile::Value.
+    #[allow(clippy::manual_map)]
+    pub fn from_tile_value(v: &'a tile::Value) -> Option<Self> {
         if let Some(s) = &v.string_value {
-            Some(Value::String(s.clone()))
+            Some(ValueRef::String(s))
         } else if let Some(f) = v.float_value {
-            Some(Value::Float(f.to_ne_bytes()))
+            Some(ValueRef::Float(f.to_ne_bytes()))
         } else if let Some(d) = v.double_value {
-            Some(Value::Double(d.to_ne_bytes()))
+            Some(ValueRef::Double(d.to_ne_bytes()))
         } else if let Some(i) = v.int_value {
-            Some(Value::Int(i))
+            Some(ValueRef::Int(i))
         } else if let Some(u) = v.uint_value {
-            Some(Value::Uint(u))
+            Some(ValueRef::Uint(u))
         } else if let Some(s) = v.sint_value {
-            Some(Value::SInt(s))
+            Some(ValueRef::SInt(s))
         } else if let Some(b) = v.bool_value {
-            Some(Value::Bool(b))
+            Some(ValueRef::Bool(b))
         } else {
             None
         }
     }
 }
 
+impl From<ValueRef<'_>> for Value {
+    fn from(v: ValueRef<'_>) -> Self {
+        match v {
+            ValueRef::String(v) => Value::String(v.to_string()),
+            ValueRef::Float(v) => Value::Float(v),
+            ValueRef::Double(v) => Value::Double(v),
+            ValueRef::Int(v) => Value::Int(v),
+            ValueRef::Uint(v) => Value::Uint(v),
+            ValueRef::SInt(v) => Value::SInt(v),
+            ValueRef::Bool(v) => Value::Bool(v),
+        }
+    }
+}
+
 /// Utility for decoding MVT tags (attributes).
 pub struct TagsDecoder<'a> {
     keys: &'a [String],
@@ -195,35 +242,97 @@ impl<'a> TagsDecoder<'a> {
     }
 
     /// Decodes tags into a vector of key-value pairs.
-    pub fn decode(&self, tags: &[u32]) -> Result<Vec<(&'a str, Value)>, String> {
-        if !tags.len().is_multiple_of(2) {
-            return Err("Tags array must have even length".to_string());
-        }
+    pub fn decode(&self, tags: &[u32]) -> Result<Vec<(&'a str, Value)>> {
+        self.iter(tags)
+            .map(|tag| tag.map(|(key, value)| (key, value.into())))
+            .collect()
+    }
 
-        let mut result = Vec::with_capacity(tags.len() / 2);
+    /// Returns an iterator that lazily decodes tags into borrowed key-value pairs.
+    ///
+    /// If the tags array has an odd length, the iterator yields a single error.
+    pub fn iter<'b>(&self, tags: &'b [u32]) -> Tags<'a, 'b> {
+        Tags {
+            keys: self.keys,
+            values: self.values,
+            tags,
+            odd: !tags.len().is_multiple_of(2),
+        }
+    }
 
+    /// Finds the value of a single key without decoding the other tags.
+    ///
+    /// Only the key of each tag is resolved until a match is found. Returns the first
+    /// match, or `None` if the feature doesn't have the key.
+    pub fn get(&self, tags: &[u32], key: &str) -> Result<Option<ValueRef<'a>>> {
+        if !tags.len().is_multiple_of(2) {
+            return Err(Error::OddTagsLength { len: tags.len() });
+        }
         for chunk in tags.chunks_exact(2) {
             let key_idx = chunk[0] as usize;
-            let value_idx = chunk[1] as usize;
-
-            if key_idx >= self.keys.len() {
-                return Err(format!("Key index {} out of bounds", key_idx));
-            }
-            if value_idx >= self.values.len() {
-                return Err(format!("Value index {} out of bounds", value_idx));
+            let k = self
+                .keys
+                .get(key_idx)
+                .ok_or(Error::KeyIndexOutOfBounds { index: key_idx })?;
+            if k == key {
+                return decode_value(self.values, chunk[1] as usize).map(Some);
             }
+        }
+        Ok(None)
+    }
+}
+
+/// Iterator over the decoded tags of a feature, created by [`TagsDecoder::iter`].
+pub struct Tags<'a, 'b> {
+    keys: &'a [String],
+    values: &'a [tile::Value],
+    tags: &'b [u32],
+    odd: bool,
+}
 
-            let key = &self.keys[key_idx];
-            let value = Value::from_tile_value(&self.values[value_idx])
-                .ok_or_else(|| format!("Invalid tile value at index {}", value_idx))?;
+impl<'a> Iterator for Tags<'a, '_> {
+    type Item = Result<(&'a str, ValueRef<'a>)>;
 
-            result.push((key.as_str(), value));
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.odd {
+            self.odd = false;
+            let len = self.tags.len();
+            self.tags = &[];
+            return Some(Err(Error::OddTagsLength { len }));
         }
+        let [key_idx, value_idx, rest @ ..] = self.tags else {
+            return None;
+        };
+        self.tags = rest;
 
-        Ok(result)
+        let key_idx = *key_idx as usize;
+        let Some(key) = self.keys.get(key_idx) else {
+            self.tags = &[];
+            return Some(Err(Error::KeyIndexOutOfBounds { index: key_idx }));
+        };
+        match decode_value(self.values, *value_idx as usize) {
+            Ok(value) => Some(Ok((key, value))),
+            Err(err) => {
+                self.tags = &[];
+                Some(Err(err))
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.tags.len() / 2 + self.odd as usize;
+        (n.min(1), Some(n))
     }
 }
 
+#[inline]
+fn decode_value(values: &[tile::Value], index: usize) -> Result<ValueRef<'_>> {
+    let value = values
+        .get(index)
+        .ok_or(Error::ValueIndexOutOfBounds { index })?;
+    ValueRef::from_tile_value(value).ok_or(Error::InvalidValue { index })
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -380,7 +489,7 @@ mod test {
         let decoder = TagsDecoder::new(&keys, &values);
 
         let result = decoder.decode(&[0]);
-        assert!(result.is_err());
+        assert_eq!(result, Err(Error::OddTagsLength { len: 1 }));
     }
 
     #[test]
@@ -393,7 +502,7 @@ mod test {
         let decoder = TagsDecoder::new(&keys, &values);
 
         let result = decoder.decode(&[99, 0]);
-        assert!(result.is_err());
+        assert_eq!(result, Err(Error::KeyIndexOutOfBounds { index: 99 }));
     }
 
     #[test]
@@ -406,6 +515,87 @@ mod test {
         let decoder = TagsDecoder::new(&keys, &values);
 
         let result = decoder.decode(&[0, 99]);
-        assert!(result.is_err());
+        assert_eq!(result, Err(Error::ValueIndexOutOfBounds { index: 99 }));
+    }
+
+    #[test]
+    fn test_tags_decoder_error_invalid_value() {
+        let keys = vec!["key".to_string()];
+        let values = vec![tile::Value::default()];
+        let decoder = TagsDecoder::new(&keys, &values);
+
+        let result = decoder.decode(&[0, 0]);
+        assert_eq!(result, Err(Error::InvalidValue { index: 0 }));
+    }
+
+    #[test]
+    fn test_tags_decoder_iter_and_get() {
+        let mut encoder = TagsEncoder::new();
+        encoder.add("name", "road");
+        encoder.add("lanes", 4u32);
+        encoder.add("oneway", true);
+
+        let tags = encoder.take_tags();
+        let (keys, values) = encoder.into_keys_and_values();
+        let decoder = TagsDecoder::new(&keys, &values);
+
+        let decoded: Vec<_> = decoder.iter(&tags).collect::<Result<_>>().unwrap();
+        assert_eq!(
+            decoded,
+            [
+                ("name", ValueRef::String("road")),
+                ("lanes", ValueRef::Uint(4)),
+                ("oneway", ValueRef::Bool(true)),
+            ]
+        );
+        // the string borrows from the values dictionary
+        let ValueRef::String(name) = decoded[0].1 else {
+            panic!()
+        };
+        assert!(std::ptr::eq(
+            name,
+            values[0].string_value.as_deref().unwrap()
+        ));
+
+        assert_eq!(decoder.get(&tags, "lanes"), Ok(Some(ValueRef::Uint(4))));
+        assert_eq!(decoder.get(&tags, "missing"), Ok(None));
+        assert_eq!(Value::from(decoded[0].1), Value::from("road"));
+        assert_eq!(Value::from("road").as_value_ref(), decoded[0].1);
+    }
+
+    #[test]
+    fn test_tags_decoder_iter_errors() {
+        let keys = vec!["key".to_string()];
+        let values = vec![tile::Value {
+            string_value: Some("value".to_string()),
+            ..Default::default()
+        }];
+        let decoder = TagsDecoder::new(&keys, &values);
+
+        let result: Vec<_> = decoder.iter(&[0, 0, 0]).collect();
+        assert_eq!(result, [Err(Error::OddTagsLength { len: 3 })]);
+
+        let result: Vec<_> = decoder.iter(&[0, 0, 0, 5, 0, 0]).collect();
+        assert_eq!(
+            result,
+            [
+                Ok(("key", ValueRef::String("value"))),
+                Err(Error::ValueIndexOutOfBounds { index: 5 })
+            ]
+        );
+
+        // get() only resolves the values of matching keys
+        assert_eq!(
+            decoder.get(&[0, 0, 1, 5], "key"),
+            Ok(Some(ValueRef::String("value")))
+        );
+        assert_eq!(
+            decoder.get(&[1, 0], "key"),
+            Err(Error::KeyIndexOutOfBounds { index: 1 })
+        );
+        assert_eq!(
+            decoder.get(&[0], "key"),
+            Err(Error::OddTagsLength { len: 1 })
+        );
     }
 }
@@ -1,5 +1,93 @@
 //! Conversion between Tile ID and XYZ tile coordinate.
+//!
+//! Three schemes are supported: Hilbert IDs (as used by PMTiles), Morton (Z-order) IDs,
+//! and quadkey strings. The functions in this module convert directly between schemes.
 
 pub mod hilbert;
+pub mod morton;
+pub mod quadkey;
 
 pub use hilbert::*;
+pub use morton::*;
+pub use quadkey::*;
+
+/// Converts a Hilbert tile ID to a Morton tile ID.
+#[inline]
+pub fn hilbert_to_morton(id: u64) -> u64 {
+    let (z, x, y) = hilbert_to_zxy(id);
+    zxy_to_morton(z, x, y)
+}
+
+/// Converts a Morton tile ID to a Hilbert tile ID.
+#[inline]
+pub fn morton_to_hilbert(id: u64) -> u64 {
+    let (z, x, y) = morton_to_zxy(id);
+    zxy_to_hilbert(z, x, y)
+}
+
+/// Converts a Hilbert tile ID to a quadkey.
+#[inline]
+pub fn hilbert_to_quadkey(id: u64) -> String {
+    let (z, x, y) = hilbert_to_zxy(id);
+    zxy_to_quadkey(z, x, y)
+}
+
+/// Converts a quadkey to a Hilbert tile ID. Returns `None` if the quadkey is invalid.
+#[inline]
+pub fn quadkey_to_hilbert(quadkey: &str) -> Option<u64> {
+    quadkey_to_zxy(quadkey).map(|(z, x, y)| zxy_to_hilbert(z, x, y))
+}
+
+/// Converts a Morton tile ID to a quadkey.
+#[inline]
+pub fn morton_to_quadkey(id: u64) -> String {
+    let (z, x, y) = morton_to_zxy(id);
+    zxy_to_quadkey(z, x, y)
+}
+
+/// Converts a quadkey to a Morton tile ID. Returns `None` if the quadkey is invalid.
+#[inline]
+pub fn quadkey_to_morton(quadkey: &str) -> Option<u64> {
+    quadkey_to_zxy(quadkey).map(|(z, x, y)| zxy_to_morton(z, x, y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_between_schemes() {
+        for (z, x, y) in [
+            (0, 0, 0),
+            (1, 1, 0),
+            (3, 5, 2),
+            (18, 1, 1),
+            (31, 100, 100),
+            (31, (1 << 31) - 1, 0),
+        ] {
+            let hilbert = zxy_to_hilbert(z, x, y);
+            let morton = zxy_to_morton(z, x, y);
+            let quadkey = zxy_to_quadkey(z, x, y);
+            assert_eq!(hilbert_to_morton(hilbert), morton);
+            assert_eq!(morton_to_hilbert(morton), hilbert);
+            assert_eq!(hilbert_to_quadkey(hilbert), quadkey);
+            assert_eq!(quadkey_to_hilbert(&quadkey), Some(hilbert));
+            assert_eq!(morton_to_quadkey(morton), quadkey);
+            assert_eq!(quadkey_to_morton(&quadkey), Some(morton));
+        }
+        assert_eq!(quadkey_to_morton("4"), None);
+    }
+
+    #[test]
+    fn morton_order_matches_quadkey_order() {
+        let mut tiles: Vec<(u8, u32, u32)> = (0..8)
+            .flat_map(|x| (0..8).map(move |y| (3, x, y)))
+            .collect();
+        tiles.sort_by_key(|&(z, x, y)| zxy_to_morton(z, x, y));
+        let quadkeys: Vec<String> = tiles
+            .iter()
+            .map(|&(z, x, y)| zxy_to_quadkey(z, x, y))
+            .collect();
+        assert!(quadkeys.is_sorted());
+    }
+}
@@ -0,0 +1,95 @@
+//! Tile ID based on Morton code (Z-order curve)
+//!
+//! Like the Hilbert IDs, tiles are numbered level by level, starting from zoom 0.
+//! Within a level, the bits of x and y are interleaved with x in the lower bit,
+//! so that the order matches quadkeys.
+
+#[inline]
+pub fn morton_to_zxy(id: u64) -> (u8, u32, u32) {
+    let z = (((u64::BITS - (3 * id + 1).leading_zeros()) - 1) / 2) as u8;
+    let acc = ((1 << (z * 2)) - 1) / 3;
+    let pos = id - acc;
+    (z, compact(pos), compact(pos >> 1))
+}
+
+#[inline]
+pub fn zxy_to_morton(z: u8, x: u32, y: u32) -> u64 {
+    let acc = ((1 << (z * 2)) - 1) / 3;
+    acc + (spread(x) | (spread(y) << 1))
+}
+
+/// Inserts a zero bit above each bit of `v`.
+#[inline]
+const fn spread(v: u32) -> u64 {
+    let mut v = v as u64;
+    v = (v | (v << 16)) & 0x0000_ffff_0000_ffff;
+    v = (v | (v << 8)) & 0x00ff_00ff_00ff_00ff;
+    v = (v | (v << 4)) & 0x0f0f_0f0f_0f0f_0f0f;
+    v = (v | (v << 2)) & 0x3333_3333_3333_3333;
+    (v | (v << 1)) & 0x5555_5555_5555_5555
+}
+
+/// Inverse of [`spread`], taking the even bits of `v`.
+#[inline]
+const fn compact(v: u64) -> u32 {
+    let mut v = v & 0x5555_5555_5555_5555;
+    v = (v | (v >> 1)) & 0x3333_3333_3333_3333;
+    v = (v | (v >> 2)) & 0x0f0f_0f0f_0f0f_0f0f;
+    v = (v | (v >> 4)) & 0x00ff_00ff_00ff_00ff;
+    v = (v | (v >> 8)) & 0x0000_ffff_0000_ffff;
+    (v | (v >> 16)) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let fixture = vec![
+            // ((x, y, z), expected_tile_id)
+            //
+            // z = 0
+            ((0, 0, 0), 0),
+            // z = 1
+            ((1, 0, 0), 1),
+            ((1, 1, 0), 2),
+            ((1, 0, 1), 3),
+            ((1, 1, 1), 4),
+            // z = 2
+            ((2, 0, 0), 5),
+            ((2, 1, 1), 8),
+            ((2, 2, 0), 9),
+            ((2, 3, 3), 20),
+            // z = 3
+            ((3, 0, 0), 21),
+            ((3, 7, 0), 21 + 0b010101),
+            // z = 18 (tileId exceeds u32)
+            ((18, 1, 1), 22906492248),
+            // z = 31
+            ((31, 100, 100), 1537228672809144709),
+            ((31, (1 << 31) - 1, (1 << 31) - 1), 6148914691236517204),
+        ];
+
+        for ((z, x, y), expected_tile_id) in fixture {
+            let tile_id = zxy_to_morton(z, x, y);
+            assert_eq!(tile_id, expected_tile_id);
+            assert_eq!(morton_to_zxy(tile_id), (z, x, y));
+        }
+    }
+
+    #[test]
+    fn ids_are_contiguous() {
+        let mut expected = 0;
+        for z in 0..5u8 {
+            let mut ids: Vec<u64> = (0..1 << z)
+                .flat_map(|x| (0..1 << z).map(move |y| zxy_to_morton(z, x, y)))
+                .collect();
+            ids.sort();
+            for id in ids {
+                assert_eq!(id, expected);
+                expected += 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! Tile ID based on quadkey strings (compatible with Bing Maps)
+
+use crate::TileZXY;
+
+/// Converts a tile to its quadkey. The quadkey of the zoom-0 tile is the empty string.
+pub fn zxy_to_quadkey(z: u8, x: u32, y: u32) -> String {
+    (0..z)
+        .rev()
+        .map(|a| {
+            let digit = ((x >> a) & 1) | (((y >> a) & 1) << 1);
+            char::from(b'0' + digit as u8)
+        })
+        .collect()
+}
+
+/// Converts a quadkey to a tile. Returns `None` if the quadkey is invalid or longer than 31 digits.
+pub fn quadkey_to_zxy(quadkey: &str) -> Option<TileZXY> {
+    if quadkey.len() > 31 {
+        return None;
+    }
+    quadkey.bytes().try_fold((0, 0, 0), |(z, x, y), c| {
+        let digit = match c {
+            b'0'..=b'3' => (c - b'0') as u32,
+            _ => return None,
+        };
+        Some((z + 1, (x << 1) | (digit & 1), (y << 1) | (digit >> 1)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let fixture = [
+            ((0, 0, 0), ""),
+            ((1, 1, 0), "1"),
+            ((1, 0, 1), "2"),
+            ((3, 3, 5), "213"),
+            (
+                (31, (1 << 31) - 1, (1 << 31) - 1),
+                "3333333333333333333333333333333",
+            ),
+        ];
+        for ((z, x, y), quadkey) in fixture {
+            assert_eq!(zxy_to_quadkey(z, x, y), quadkey);
+            assert_eq!(quadkey_to_zxy(quadkey), Some((z, x, y)));
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(quadkey_to_zxy("0124"), None);
+        assert_eq!(quadkey_to_zxy("a"), None);
+        assert_eq!(quadkey_to_zxy(&"0".repeat(32)), None);
+    }
+}
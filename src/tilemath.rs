@@ -0,0 +1,259 @@
+//! Tile hierarchy and neighborhood utilities for XYZ tiles.
+
+use std::ops::Range;
+
+use crate::TileZXY;
+use crate::tileid::zxy_to_hilbert;
+use crate::webmercator::lnglat_to_zxy;
+
+/// Maximum latitude covered by Web Mercator tiles.
+const MAX_LAT: f64 = 85.0511287798066;
+
+/// Returns the parent of the tile, or `None` for the zoom-0 tile.
+#[inline]
+pub fn parent((z, x, y): TileZXY) -> Option<TileZXY> {
+    (z > 0).then(|| (z - 1, x >> 1, y >> 1))
+}
+
+/// Returns the ancestor of the tile at the given zoom level.
+///
+/// Returns the tile itself if `zoom` equals its zoom level, and `None` if `zoom` is greater.
+#[inline]
+pub fn ancestor((z, x, y): TileZXY, zoom: u8) -> Option<TileZXY> {
+    let d = z.checked_sub(zoom)?;
+    Some((zoom, x >> d, y >> d))
+}
+
+/// Returns the four children of the tile, in the order (0, 0), (1, 0), (0, 1), (1, 1).
+///
+/// # Panics
+///
+/// Panics if the tile is at zoom level 31.
+#[inline]
+pub fn children((z, x, y): TileZXY) -> [TileZXY; 4] {
+    assert!(z < 31, "tiles at zoom level 31 have no children");
+    let (z, x, y) = (z + 1, x << 1, y << 1);
+    [(z, x, y), (z, x + 1, y), (z, x, y + 1), (z, x + 1, y + 1)]
+}
+
+/// Returns an iterator over the descendants of the tile at the given zoom level, row by row.
+///
+/// Yields the tile itself if `zoom` equals its zoom level, and nothing if `zoom` is
+/// smaller or greater than 31.
+pub fn descendants((z, x, y): TileZXY, zoom: u8) -> impl Iterator<Item = TileZXY> {
+    let (xs, ys) = match zoom.checked_sub(z) {
+        Some(d) if zoom <= 31 => {
+            let n = 1u32 << d;
+            ((x << d)..(x << d) + n, (y << d)..(y << d) + n)
+        }
+        _ => (0..0, 0..0),
+    };
+    ys.flat_map(move |y| xs.clone().map(move |x| (zoom, x, y)))
+}
+
+/// Returns the other tiles sharing the same parent.
+pub fn siblings(tile: TileZXY) -> Vec<TileZXY> {
+    match parent(tile) {
+        Some(parent) => children(parent)
+            .into_iter()
+            .filter(|&t| t != tile)
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Returns the (up to) eight surrounding tiles, row by row.
+///
+/// X coordinates wrap around the antimeridian; there are no neighbors beyond the poles.
+/// At low zoom levels where wrapping reaches the same tile twice, each tile is returned once.
+pub fn neighbors((z, x, y): TileZXY) -> Vec<TileZXY> {
+    let n = 1i64 << z;
+    let mut tiles = Vec::with_capacity(8);
+    for dy in -1..=1 {
+        let ny = y as i64 + dy;
+        if !(0..n).contains(&ny) {
+            continue;
+        }
+        for dx in -1..=1 {
+            let tile = (z, (x as i64 + dx).rem_euclid(n) as u32, ny as u32);
+            if tile != (z, x, y) && !tiles.contains(&tile) {
+                tiles.push(tile);
+            }
+        }
+    }
+    tiles
+}
+
+/// Converts between XYZ and TMS tile coordinates. The conversion is its own inverse.
+#[inline]
+pub fn flip_y((z, x, y): TileZXY) -> TileZXY {
+    (z, x, ((1u64 << z) - 1 - y as u64) as u32)
+}
+
+/// Returns an iterator over the tiles at the zoom level that intersect the bounding box, row by row.
+///
+/// The bounding box is (west, south, east, north) in degrees. If west is greater than east,
+/// the box is taken to cross the antimeridian. Returns an empty iterator if `zoom` is greater
+/// than 31.
+pub fn tiles_in_bbox(
+    [west, south, east, north]: [f64; 4],
+    zoom: u8,
+) -> impl Iterator<Item = TileZXY> {
+    let ranges = (zoom <= 31).then(|| {
+        let max = (1u32 << zoom) - 1;
+        let corner = |lng: f64, lat: f64| {
+            let (_, x, y) =
+                lnglat_to_zxy(zoom, lng.clamp(-180.0, 180.0), lat.clamp(-MAX_LAT, MAX_LAT));
+            (x.min(max), y.min(max))
+        };
+        let (x0, y0) = corner(west, north);
+        let (x1, y1) = corner(east, south);
+        let (first, second) = if west <= east {
+            (x0..x1 + 1, 0..0)
+        } else {
+            (x0..max + 1, 0..x1 + 1)
+        };
+        (y0..=y1, first, second)
+    });
+    ranges.into_iter().flat_map(move |(rows, first, second)| {
+        rows.flat_map(move |y| {
+            first
+                .clone()
+                .chain(second.clone())
+                .map(move |x| (zoom, x, y))
+        })
+    })
+}
+
+/// Returns the range of Hilbert tile IDs covering the descendants of the tile at the given zoom level.
+///
+/// Since the Hilbert curve is hierarchical, the descendants at each zoom level are contiguous in
+/// PMTiles order, so a subtree can be fetched from an ID-sorted store with one range scan per level.
+/// Returns an empty range if `zoom` is smaller than the tile's zoom level or greater than 31.
+pub fn hilbert_range((z, x, y): TileZXY, zoom: u8) -> Range<u64> {
+    let Some(d) = zoom.checked_sub(z).filter(|_| zoom <= 31) else {
+        return 0..0;
+    };
+    let level_start = |z: u8| ((1u64 << (z * 2)) - 1) / 3;
+    let position = zxy_to_hilbert(z, x, y) - level_start(z);
+    let start = level_start(zoom) + (position << (2 * d));
+    start..start + (1u64 << (2 * d))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parent_and_ancestor() {
+        assert_eq!(parent((0, 0, 0)), None);
+        assert_eq!(parent((3, 5, 2)), Some((2, 2, 1)));
+        assert_eq!(ancestor((3, 5, 2), 1), Some((1, 1, 0)));
+        assert_eq!(ancestor((3, 5, 2), 3), Some((3, 5, 2)));
+        assert_eq!(ancestor((3, 5, 2), 4), None);
+        assert_eq!(ancestor((31, (1 << 31) - 1, 0), 0), Some((0, 0, 0)));
+    }
+
+    #[test]
+    fn test_children_and_descendants() {
+        assert_eq!(
+            children((1, 1, 0)),
+            [(2, 2, 0), (2, 3, 0), (2, 2, 1), (2, 3, 1)]
+        );
+        for child in children((5, 10, 20)) {
+            assert_eq!(parent(child), Some((5, 10, 20)));
+        }
+
+        let tiles: Vec<_> = descendants((1, 1, 0), 3).collect();
+        assert_eq!(tiles.len(), 16);
+        assert_eq!(tiles[0], (3, 4, 0));
+        assert_eq!(tiles[15], (3, 7, 3));
+        assert!(tiles.iter().all(|&t| ancestor(t, 1) == Some((1, 1, 0))));
+        assert_eq!(descendants((1, 1, 0), 1).collect::<Vec<_>>(), [(1, 1, 0)]);
+        assert_eq!(descendants((1, 1, 0), 0).count(), 0);
+        assert_eq!(descendants((30, 0, 0), 31).count(), 4);
+    }
+
+    #[test]
+    fn test_siblings() {
+        assert_eq!(siblings((0, 0, 0)), []);
+        assert_eq!(siblings((2, 3, 1)), [(2, 2, 0), (2, 3, 0), (2, 2, 1)]);
+    }
+
+    #[test]
+    fn test_neighbors() {
+        assert_eq!(neighbors((0, 0, 0)), []);
+        assert_eq!(neighbors((1, 0, 0)), [(1, 1, 0), (1, 1, 1), (1, 0, 1)]);
+        assert_eq!(
+            neighbors((2, 1, 1)),
+            [
+                (2, 0, 0),
+                (2, 1, 0),
+                (2, 2, 0),
+                (2, 0, 1),
+                (2, 2, 1),
+                (2, 0, 2),
+                (2, 1, 2),
+                (2, 2, 2)
+            ]
+        );
+        // wraps around the antimeridian, but not the poles
+        assert_eq!(
+            neighbors((3, 7, 7)),
+            [(3, 6, 6), (3, 7, 6), (3, 0, 6), (3, 6, 7), (3, 0, 7)]
+        );
+    }
+
+    #[test]
+    fn test_flip_y() {
+        assert_eq!(flip_y((0, 0, 0)), (0, 0, 0));
+        assert_eq!(flip_y((3, 1, 2)), (3, 1, 5));
+        assert_eq!(flip_y(flip_y((31, 7, 12345))), (31, 7, 12345));
+    }
+
+    #[test]
+    fn test_tiles_in_bbox() {
+        assert_eq!(
+            tiles_in_bbox([-180.0, -90.0, 180.0, 90.0], 1).collect::<Vec<_>>(),
+            [(1, 0, 0), (1, 1, 0), (1, 0, 1), (1, 1, 1)]
+        );
+        let tiles: Vec<_> = tiles_in_bbox([138.28, 37.10, 138.33, 37.16], 13).collect();
+        assert_eq!(
+            tiles,
+            [
+                (13, 7242, 3184),
+                (13, 7243, 3184),
+                (13, 7242, 3185),
+                (13, 7243, 3185)
+            ]
+        );
+        // crossing the antimeridian
+        let tiles: Vec<_> = tiles_in_bbox([170.0, 10.0, -170.0, 20.0], 3).collect();
+        assert_eq!(tiles, [(3, 7, 3), (3, 0, 3)]);
+        // zoom levels beyond 31 have no tiles
+        let tiles: Vec<_> = tiles_in_bbox([138.3, 37.1, 138.3, 37.1], 31).collect();
+        assert_eq!(tiles, [lnglat_to_zxy(31, 138.3, 37.1)]);
+        assert!(tiles[0].1 > 1 << 30);
+        assert_eq!(tiles_in_bbox([-180.0, -90.0, 180.0, 90.0], 32).count(), 0);
+    }
+
+    #[test]
+    fn test_hilbert_range() {
+        for tile in [(0, 0, 0), (1, 1, 0), (3, 5, 2), (4, 15, 0)] {
+            for zoom in tile.0..=tile.0 + 3 {
+                let range = hilbert_range(tile, zoom);
+                assert_eq!(range.end - range.start, 1 << (2 * (zoom - tile.0)));
+                for (z, x, y) in descendants(tile, zoom) {
+                    assert!(range.contains(&zxy_to_hilbert(z, x, y)));
+                }
+            }
+        }
+        assert_eq!(hilbert_range((0, 0, 0), 0), 0..1);
+        assert_eq!(hilbert_range((1, 0, 0), 1), 1..2);
+        assert!(hilbert_range((2, 0, 0), 1).is_empty());
+
+        let range = hilbert_range((30, 5, 7), 31);
+        assert_eq!(range.end - range.start, 4);
+        assert!(range.contains(&zxy_to_hilbert(31, 11, 14)));
+    }
+}
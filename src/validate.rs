@@ -0,0 +1,753 @@
+//! Validation of tiles against the MVT specification.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use prost::Message;
+
+use crate::error::{Error, Result};
+use crate::geometry::{
+    Command, Commands, GEOM_COMMAND_CLOSE_PATH, GEOM_COMMAND_LINE_TO, GEOM_COMMAND_MOVE_TO,
+    RawCommand, calculate_signed_area,
+};
+use crate::vector_tile::{
+    Tile,
+    tile::{GeomType, Layer, Value},
+};
+
+/// Severity of a [`Finding`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Discouraged by the specification, but readable by most decoders.
+    Warning,
+    /// Violates the specification.
+    Error,
+}
+
+/// Kind of a specification violation.
+#[derive(Debug, PartialEq)]
+pub enum Issue {
+    /// The tile could not be decoded as protobuf.
+    InvalidProtobuf(Error),
+    /// The layer has an empty name.
+    MissingLayerName,
+    /// Another layer in the tile has the same name.
+    DuplicateLayerName(String),
+    /// The layer version is neither 1 nor 2.
+    InvalidVersion(u32),
+    /// The layer has no extent set.
+    MissingExtent,
+    /// The keys dictionary contains the same key more than once.
+    DuplicateKey(String),
+    /// A value in the values dictionary does not have exactly one field set.
+    InvalidValue { index: usize, fields: usize },
+    /// The feature's geometry type is `UNKNOWN` or missing.
+    UnknownGeomType,
+    /// The feature's geometry type is not a valid `GeomType`.
+    InvalidGeomType(i32),
+    /// The tags array has an odd length.
+    OddTagsLength(usize),
+    /// A tag refers to a key that is not in the keys dictionary.
+    KeyIndexOutOfBounds(usize),
+    /// A tag refers to a value that is not in the values dictionary.
+    ValueIndexOutOfBounds(usize),
+    /// The geometry does not follow the command rules for its type.
+    InvalidGeometry(Error),
+    /// A polygon ring has zero area.
+    ZeroAreaRing { ring: usize },
+    /// The first ring of a polygon is not an exterior (clockwise) ring.
+    ///
+    /// Reported as a warning for version 1 layers, which did not specify the winding order.
+    FirstRingNotExterior,
+    /// An exterior ring intersects or touches itself.
+    SelfIntersectingRing { ring: usize },
+    /// A coordinate lies far outside the tile extent.
+    CoordinateOutOfRange([i32; 2]),
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Issue::*;
+        match self {
+            InvalidProtobuf(e) => write!(f, "invalid protobuf: {}", e),
+            MissingLayerName => write!(f, "layer has no name"),
+            DuplicateLayerName(name) => write!(f, "duplicate layer name {:?}", name),
+            InvalidVersion(v) => write!(f, "invalid layer version {}", v),
+            MissingExtent => write!(f, "layer has no extent"),
+            DuplicateKey(key) => write!(f, "duplicate key {:?} in keys dictionary", key),
+            InvalidValue { index, fields } => {
+                write!(f, "value at index {} has {} fields set", index, fields)
+            }
+            UnknownGeomType => write!(f, "geometry type is UNKNOWN"),
+            InvalidGeomType(t) => write!(f, "invalid geometry type {}", t),
+            OddTagsLength(len) => write!(f, "tags array has odd length {}", len),
+            KeyIndexOutOfBounds(idx) => write!(f, "key index {} out of bounds", idx),
+            ValueIndexOutOfBounds(idx) => write!(f, "value index {} out of bounds", idx),
+            InvalidGeometry(reason) => write!(f, "invalid geometry: {}", reason),
+            ZeroAreaRing { ring } => write!(f, "ring {} has zero area", ring),
+            FirstRingNotExterior => write!(f, "first ring of polygon is not an exterior ring"),
+            SelfIntersectingRing { ring } => write!(f, "exterior ring {} self-intersects", ring),
+            CoordinateOutOfRange([x, y]) => {
+                write!(f, "coordinate ({}, {}) is far outside the extent", x, y)
+            }
+        }
+    }
+}
+
+/// A single specification violation found by [`validate`].
+#[derive(Debug, PartialEq)]
+pub struct Finding {
+    pub severity: Severity,
+    /// Index of the layer in the tile, if the finding concerns a layer.
+    pub layer: Option<usize>,
+    /// Index of the feature in the layer, if the finding concerns a feature.
+    pub feature: Option<usize>,
+    pub issue: Issue,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.severity {
+            Severity::Warning => write!(f, "warning")?,
+            Severity::Error => write!(f, "error")?,
+        }
+        if let Some(layer) = self.layer {
+            write!(f, ": layer {}", layer)?;
+        }
+        if let Some(feature) = self.feature {
+            write!(f, ": feature {}", feature)?;
+        }
+        write!(f, ": {}", self.issue)
+    }
+}
+
+/// Options for [`validate_with`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValidateOptions {
+    /// How far outside the tile coordinates may lie before being reported, as a fraction of the extent.
+    pub max_buffer_ratio: f64,
+    /// Whether to check exterior rings for self-intersections (quadratic in the number of vertices).
+    pub check_self_intersections: bool,
+}
+
+impl Default for ValidateOptions {
+    fn default() -> Self {
+        Self {
+            max_buffer_ratio: 1.0,
+            check_self_intersections: true,
+        }
+    }
+}
+
+/// Validates an encoded tile with the default options.
+pub fn validate_bytes(buf: &[u8]) -> Vec<Finding> {
+    match Tile::decode(buf) {
+        Ok(tile) => validate(&tile),
+        Err(e) => vec![Finding {
+            severity: Severity::Error,
+            layer: None,
+            feature: None,
+            issue: Issue::InvalidProtobuf(e.into()),
+        }],
+    }
+}
+
+/// Validates a tile with the default options and returns all findings.
+pub fn validate(tile: &Tile) -> Vec<Finding> {
+    validate_with(tile, &ValidateOptions::default())
+}
+
+/// Validates a tile and returns all findings.
+pub fn validate_with(tile: &Tile, options: &ValidateOptions) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut names = HashSet::new();
+
+    for (layer_idx, layer) in tile.layers.iter().enumerate() {
+        let mut report = |severity, feature, issue| {
+            findings.push(Finding {
+                severity,
+                layer: Some(layer_idx),
+                feature,
+                issue,
+            })
+        };
+
+        if layer.name.is_empty() {
+            report(Severity::Error, None, Issue::MissingLayerName);
+        } else if !names.insert(layer.name.as_str()) {
+            report(
+                Severity::Error,
+                None,
+                Issue::DuplicateLayerName(layer.name.clone()),
+            );
+        }
+        validate_layer(layer, options, &mut report);
+    }
+
+    findings
+}
+
+fn validate_layer(
+    layer: &Layer,
+    options: &ValidateOptions,
+    report: &mut impl FnMut(Severity, Option<usize>, Issue),
+) {
+    if !matches!(layer.version, 1 | 2) {
+        report(Severity::Error, None, Issue::InvalidVersion(layer.version));
+    }
+    if layer.extent.is_none() {
+        report(Severity::Warning, None, Issue::MissingExtent);
+    }
+
+    let mut keys = HashSet::new();
+    for key in &layer.keys {
+        if !keys.insert(key.as_str()) {
+            report(Severity::Warning, None, Issue::DuplicateKey(key.clone()));
+        }
+    }
+    for (index, value) in layer.values.iter().enumerate() {
+        let fields = count_value_fields(value);
+        if fields != 1 {
+            report(Severity::Error, None, Issue::InvalidValue { index, fields });
+        }
+    }
+
+    let extent = layer.extent.unwrap_or(4096) as f64;
+    let margin = extent * options.max_buffer_ratio;
+    let range = ((-margin).floor() as i64, (extent + margin).ceil() as i64);
+
+    for (feature_idx, feature) in layer.features.iter().enumerate() {
+        let mut report = |severity, issue| report(severity, Some(feature_idx), issue);
+
+        if !feature.tags.len().is_multiple_of(2) {
+            report(Severity::Error, Issue::OddTagsLength(feature.tags.len()));
+        }
+        for pair in feature.tags.chunks_exact(2) {
+            if pair[0] as usize >= layer.keys.len() {
+                report(
+                    Severity::Error,
+                    Issue::KeyIndexOutOfBounds(pair[0] as usize),
+                );
+            }
+            if pair[1] as usize >= layer.values.len() {
+                report(
+                    Severity::Error,
+                    Issue::ValueIndexOutOfBounds(pair[1] as usize),
+                );
+            }
+        }
+
+        let geom_type = match feature.r#type {
+            None | Some(0) => {
+                report(Severity::Warning, Issue::UnknownGeomType);
+                continue;
+            }
+            Some(t) => match GeomType::try_from(t) {
+                Ok(geom_type) => geom_type,
+                Err(_) => {
+                    report(Severity::Error, Issue::InvalidGeomType(t));
+                    continue;
+                }
+            },
+        };
+
+        let paths = match parse_paths(&feature.geometry) {
+            Ok(paths) => paths,
+            Err(err) => {
+                report(Severity::Error, Issue::InvalidGeometry(err));
+                continue;
+            }
+        };
+        if let Err(err) = check_command_rules(geom_type, &paths, feature.geometry.len()) {
+            report(Severity::Error, Issue::InvalidGeometry(err));
+            continue;
+        }
+
+        if let Some(&[x, y]) = paths.iter().flat_map(|path| &path.coords).find(|[x, y]| {
+            !(range.0..=range.1).contains(&(*x as i64))
+                || !(range.0..=range.1).contains(&(*y as i64))
+        }) {
+            report(Severity::Warning, Issue::CoordinateOutOfRange([x, y]));
+        }
+
+        if geom_type == GeomType::Polygon {
+            for (ring_idx, path) in paths.iter().enumerate() {
+                let area = calculate_signed_area(&path.coords);
+                if area == 0.0 {
+                    report(Severity::Error, Issue::ZeroAreaRing { ring: ring_idx });
+                } else if ring_idx == 0 && area < 0.0 {
+                    // version 1 did not specify the winding order of rings
+                    let severity = match layer.version {
+                        1 => Severity::Warning,
+                        _ => Severity::Error,
+                    };
+                    report(severity, Issue::FirstRingNotExterior);
+                } else if area > 0.0
+                    && options.check_self_intersections
+                    && is_self_intersecting(&path.coords)
+                {
+                    report(
+                        Severity::Error,
+                        Issue::SelfIntersectingRing { ring: ring_idx },
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn count_value_fields(value: &Value) -> usize {
+    [
+        value.string_value.is_some(),
+        value.float_value.is_some(),
+        value.double_value.is_some(),
+        value.int_value.is_some(),
+        value.uint_value.is_some(),
+        value.sint_value.is_some(),
+        value.bool_value.is_some(),
+    ]
+    .into_iter()
+    .filter(|&set| set)
+    .count()
+}
+
+/// A path started by a MoveTo command, with the commands that built it.
+struct Path {
+    commands: Vec<RawCommand>,
+    coords: Vec<[i32; 2]>,
+}
+
+/// Splits the geometry into paths at each MoveTo command, checking only the command encoding.
+fn parse_paths(geometry: &[u32]) -> Result<Vec<Path>> {
+    fn add_command(paths: &mut Vec<Path>, raw: RawCommand) -> Result<()> {
+        if raw.id == GEOM_COMMAND_MOVE_TO {
+            paths.push(Path {
+                commands: Vec::new(),
+                coords: Vec::new(),
+            });
+        }
+        let Some(path) = paths.last_mut() else {
+            return Err(Error::UnexpectedCommand {
+                expected: GEOM_COMMAND_MOVE_TO,
+                found: raw.id,
+                offset: raw.offset,
+            });
+        };
+        path.commands.push(raw);
+        Ok(())
+    }
+
+    let mut paths = Vec::new();
+    // offset of the command integer the last event belongs to
+    let mut last_offset = None;
+
+    let mut commands = Commands::new(geometry);
+    commands.set_include_empty(true);
+    while let Some(command) = commands.next() {
+        let command = command?;
+        let raw = commands.raw_command().unwrap();
+        if last_offset != Some(raw.offset) {
+            add_command(&mut paths, raw)?;
+            last_offset = Some(raw.offset);
+        }
+        if let Command::MoveTo(coord) | Command::LineTo(coord) = command {
+            paths.last_mut().unwrap().coords.push(coord);
+        }
+    }
+    Ok(paths)
+}
+
+/// Checks the command sequence rules of MVT spec 4.3.4.
+///
+/// Each path must consist of the given commands, with counts in the given ranges.
+fn check_command_rules(geom_type: GeomType, paths: &[Path], len: usize) -> Result<()> {
+    const ANY: u32 = u32::MAX;
+    let pattern: &[(u32, u32, u32)] = match geom_type {
+        GeomType::Point => &[(GEOM_COMMAND_MOVE_TO, 1, ANY)],
+        GeomType::Linestring => &[(GEOM_COMMAND_MOVE_TO, 1, 1), (GEOM_COMMAND_LINE_TO, 1, ANY)],
+        GeomType::Polygon => &[
+            (GEOM_COMMAND_MOVE_TO, 1, 1),
+            (GEOM_COMMAND_LINE_TO, 2, ANY),
+            (GEOM_COMMAND_CLOSE_PATH, 1, 1),
+        ],
+        GeomType::Unknown => return Ok(()),
+    };
+    if paths.is_empty() {
+        return Err(Error::Truncated { offset: 0 });
+    }
+    if let (GeomType::Point, [_, second, ..]) = (geom_type, paths) {
+        // a point geometry is a single MoveTo, so any further one is invalid
+        let raw = second.commands[0];
+        return Err(Error::InvalidCommandCount {
+            command: raw.id,
+            count: raw.count,
+            offset: raw.offset,
+        });
+    }
+
+    for (i, path) in paths.iter().enumerate() {
+        // the path ends where the next one starts
+        let end = paths.get(i + 1).map_or(len, |next| next.commands[0].offset);
+        for (j, &(id, min, max)) in pattern.iter().enumerate() {
+            let Some(raw) = path.commands.get(j) else {
+                return Err(Error::Truncated { offset: end });
+            };
+            if raw.id != id {
+                return Err(Error::UnexpectedCommand {
+                    expected: id,
+                    found: raw.id,
+                    offset: raw.offset,
+                });
+            }
+            if !(min..=max).contains(&raw.count) {
+                return Err(Error::InvalidCommandCount {
+                    command: raw.id,
+                    count: raw.count,
+                    offset: raw.offset,
+                });
+            }
+        }
+        if let Some(raw) = path.commands.get(pattern.len()) {
+            return Err(Error::UnexpectedCommand {
+                expected: GEOM_COMMAND_MOVE_TO,
+                found: raw.id,
+                offset: raw.offset,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Returns true if any two non-adjacent edges of the ring intersect or touch.
+fn is_self_intersecting(ring: &[[i32; 2]]) -> bool {
+    let mut ring = ring.to_vec();
+    ring.dedup();
+    while ring.len() > 1 && ring.first() == ring.last() {
+        ring.pop();
+    }
+    let n = ring.len();
+    if n < 4 {
+        return false;
+    }
+    let edge = |i: usize| (ring[i], ring[(i + 1) % n]);
+    for i in 0..n {
+        let (a, b) = edge(i);
+        // skip the adjacent edges (i + 1 and, for the first edge, the last one)
+        for j in (i + 2)..n {
+            if i == 0 && j == n - 1 {
+                continue;
+            }
+            let (c, d) = edge(j);
+            if segments_intersect(a, b, c, d) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+fn segments_intersect(a: [i32; 2], b: [i32; 2], c: [i32; 2], d: [i32; 2]) -> bool {
+    if a[0].max(b[0]) < c[0].min(d[0])
+        || c[0].max(d[0]) < a[0].min(b[0])
+        || a[1].max(b[1]) < c[1].min(d[1])
+        || c[1].max(d[1]) < a[1].min(b[1])
+    {
+        return false;
+    }
+    let d1 = orientation(c, d, a);
+    let d2 = orientation(c, d, b);
+    let d3 = orientation(a, b, c);
+    let d4 = orientation(a, b, d);
+    if d1 * d2 < 0 && d3 * d4 < 0 {
+        return true;
+    }
+    // collinear or touching cases (bounding boxes already overlap)
+    (d1 == 0 && on_segment(c, d, a))
+        || (d2 == 0 && on_segment(c, d, b))
+        || (d3 == 0 && on_segment(a, b, c))
+        || (d4 == 0 && on_segment(a, b, d))
+}
+
+#[inline]
+fn orientation(a: [i32; 2], b: [i32; 2], c: [i32; 2]) -> i64 {
+    let v = (b[0] as i64 - a[0] as i64) * (c[1] as i64 - a[1] as i64)
+        - (b[1] as i64 - a[1] as i64) * (c[0] as i64 - a[0] as i64);
+    v.signum()
+}
+
+#[inline]
+fn on_segment(a: [i32; 2], b: [i32; 2], p: [i32; 2]) -> bool {
+    a[0].min(b[0]) <= p[0]
+        && p[0] <= a[0].max(b[0])
+        && a[1].min(b[1]) <= p[1]
+        && p[1] <= a[1].max(b[1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::GeometryEncoder;
+    use crate::tag::TagsEncoder;
+    use crate::vector_tile::tile::Feature;
+
+    fn valid_layer(name: &str) -> Layer {
+        let mut tags_enc = TagsEncoder::new();
+        let mut features = Vec::new();
+
+        let mut geom_enc = GeometryEncoder::new();
+        geom_enc.add_points([[10, 10], [20, 20]]);
+        tags_enc.add("name", "point");
+        features.push(Feature {
+            id: Some(1),
+            tags: tags_enc.take_tags(),
+            r#type: Some(GeomType::Point as i32),
+            geometry: geom_enc.into_vec(),
+        });
+
+        let mut geom_enc = GeometryEncoder::new();
+        geom_enc.add_linestring([[0, 0], [100, 100]]);
+        tags_enc.add("name", "line");
+        features.push(Feature {
+            id: Some(2),
+            tags: tags_enc.take_tags(),
+            r#type: Some(GeomType::Linestring as i32),
+            geometry: geom_enc.into_vec(),
+        });
+
+        let mut geom_enc = GeometryEncoder::new();
+        geom_enc.add_ring([[0, 0], [100, 0], [100, 100], [0, 100]]);
+        geom_enc.add_ring([[10, 10], [10, 20], [20, 20], [20, 10]]);
+        features.push(Feature {
+            id: Some(3),
+            tags: vec![],
+            r#type: Some(GeomType::Polygon as i32),
+            geometry: geom_enc.into_vec(),
+        });
+
+        let (keys, values) = tags_enc.into_keys_and_values();
+        Layer {
+            version: 2,
+            name: name.to_string(),
+            features,
+            keys,
+            values,
+            extent: Some(4096),
+        }
+    }
+
+    fn issues(findings: &[Finding]) -> Vec<&Issue> {
+        findings.iter().map(|f| &f.issue).collect()
+    }
+
+    #[test]
+    fn test_valid_tile() {
+        let tile = Tile {
+            layers: vec![valid_layer("a"), valid_layer("b")],
+        };
+        assert_eq!(validate(&tile), []);
+        assert_eq!(validate_bytes(&tile.encode_to_vec()), []);
+    }
+
+    #[test]
+    fn test_invalid_bytes() {
+        let findings = validate_bytes(&[0x1a, 0x05, 0x0a]);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].severity, Severity::Error);
+        assert!(matches!(
+            findings[0].issue,
+            Issue::InvalidProtobuf(Error::Protobuf(_))
+        ));
+    }
+
+    #[test]
+    fn test_layer_issues() {
+        let mut unnamed = valid_layer("");
+        unnamed.version = 3;
+        unnamed.extent = None;
+        unnamed.keys.push("name".to_string());
+        unnamed.values.push(Value::default());
+        unnamed.values.push(Value {
+            int_value: Some(1),
+            uint_value: Some(1),
+            ..Default::default()
+        });
+
+        let tile = Tile {
+            layers: vec![valid_layer("a"), valid_layer("a"), unnamed],
+        };
+        let findings = validate(&tile);
+        assert_eq!(
+            issues(&findings),
+            [
+                &Issue::DuplicateLayerName("a".to_string()),
+                &Issue::MissingLayerName,
+                &Issue::InvalidVersion(3),
+                &Issue::MissingExtent,
+                &Issue::DuplicateKey("name".to_string()),
+                &Issue::InvalidValue {
+                    index: 2,
+                    fields: 0
+                },
+                &Issue::InvalidValue {
+                    index: 3,
+                    fields: 2
+                },
+            ]
+        );
+        assert_eq!(findings[0].layer, Some(1));
+        assert_eq!(findings[1].layer, Some(2));
+        assert_eq!(findings[4].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn test_feature_issues() {
+        let mut layer = valid_layer("a");
+        layer.features[0].tags = vec![0, 9, 5];
+        layer.features[1].r#type = Some(7);
+        layer.features[2].r#type = None;
+        let findings = validate(&Tile {
+            layers: vec![layer],
+        });
+        assert_eq!(
+            issues(&findings),
+            [
+                &Issue::OddTagsLength(3),
+                &Issue::ValueIndexOutOfBounds(9),
+                &Issue::InvalidGeomType(7),
+                &Issue::UnknownGeomType,
+            ]
+        );
+        assert_eq!(findings[0].feature, Some(0));
+        assert_eq!(findings[2].feature, Some(1));
+        assert_eq!(findings[3].feature, Some(2));
+    }
+
+    #[test]
+    fn test_geometry_command_rules() {
+        let check = |geom_type: GeomType, geometry: Vec<u32>| {
+            let mut layer = valid_layer("a");
+            layer.features = vec![Feature {
+                id: None,
+                tags: vec![],
+                r#type: Some(geom_type as i32),
+                geometry,
+            }];
+            validate(&Tile {
+                layers: vec![layer],
+            })
+        };
+
+        // valid
+        assert_eq!(check(GeomType::Point, vec![17, 2, 2, 4, 4]), []);
+        // LineTo in point
+        assert_eq!(
+            check(GeomType::Point, vec![9, 2, 2, 10, 2, 2])[0].issue,
+            Issue::InvalidGeometry(Error::UnexpectedCommand {
+                expected: GEOM_COMMAND_MOVE_TO,
+                found: GEOM_COMMAND_LINE_TO,
+                offset: 3
+            })
+        );
+        // two MoveTo commands in point
+        assert_eq!(
+            check(GeomType::Point, vec![9, 2, 2, 9, 2, 2])[0].issue,
+            Issue::InvalidGeometry(Error::InvalidCommandCount {
+                command: GEOM_COMMAND_MOVE_TO,
+                count: 1,
+                offset: 3
+            })
+        );
+        // MoveTo(2) in linestring
+        assert_eq!(
+            check(GeomType::Linestring, vec![17, 2, 2, 4, 4, 10, 2, 2])[0].issue,
+            Issue::InvalidGeometry(Error::InvalidCommandCount {
+                command: GEOM_COMMAND_MOVE_TO,
+                count: 2,
+                offset: 0
+            })
+        );
+        // LineTo(0) in linestring
+        assert_eq!(
+            check(GeomType::Linestring, vec![9, 2, 2, 2])[0].issue,
+            Issue::InvalidGeometry(Error::InvalidCommandCount {
+                command: GEOM_COMMAND_LINE_TO,
+                count: 0,
+                offset: 3
+            })
+        );
+        // missing ClosePath
+        assert_eq!(
+            check(GeomType::Polygon, vec![9, 0, 0, 18, 20, 0, 0, 20])[0].issue,
+            Issue::InvalidGeometry(Error::Truncated { offset: 8 })
+        );
+        // truncated
+        assert!(matches!(
+            check(GeomType::Point, vec![9, 2])[0].issue,
+            Issue::InvalidGeometry(Error::Truncated { .. })
+        ));
+        // coordinate overflow
+        assert_eq!(
+            check(
+                GeomType::Linestring,
+                vec![9, 0xFFFFFFFE, 0, 10, 0xFFFFFFFE, 0]
+            )[0]
+            .issue,
+            Issue::InvalidGeometry(Error::CoordinateOverflow { offset: 4 })
+        );
+    }
+
+    #[test]
+    fn test_polygon_rings() {
+        let check_version = |version: u32, rings: &[&[[i32; 2]]]| {
+            let mut layer = valid_layer("a");
+            layer.version = version;
+            let mut geom_enc = GeometryEncoder::new();
+            for ring in rings {
+                geom_enc.add_ring(ring.iter().copied());
+            }
+            layer.features = vec![Feature {
+                id: None,
+                tags: vec![],
+                r#type: Some(GeomType::Polygon as i32),
+                geometry: geom_enc.into_vec(),
+            }];
+            let findings = validate(&Tile {
+                layers: vec![layer],
+            });
+            findings
+                .into_iter()
+                .map(|f| (f.severity, f.issue))
+                .collect::<Vec<_>>()
+        };
+        let check = |rings: &[&[[i32; 2]]]| {
+            check_version(2, rings)
+                .into_iter()
+                .map(|(_, issue)| issue)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(check(&[&[[0, 0], [10, 0], [10, 10], [0, 10]]]), []);
+        assert_eq!(
+            check(&[&[[0, 0], [0, 10], [10, 10], [10, 0]]]),
+            [Issue::FirstRingNotExterior]
+        );
+        // version 1 did not specify the winding order
+        assert_eq!(
+            check_version(1, &[&[[0, 0], [0, 10], [10, 10], [10, 0]]]),
+            [(Severity::Warning, Issue::FirstRingNotExterior)]
+        );
+        assert_eq!(
+            check(&[&[[0, 0], [10, 0], [20, 0]]]),
+            [Issue::ZeroAreaRing { ring: 0 }]
+        );
+        // bow-tie with a larger right lobe
+        assert_eq!(
+            check(&[&[[0, 10], [20, -20], [20, 20], [0, 0]]]),
+            [Issue::SelfIntersectingRing { ring: 0 }]
+        );
+        assert_eq!(
+            check(&[&[[-5000, 0], [10, 0], [10, 10], [0, 10]]]),
+            [Issue::CoordinateOutOfRange([-5000, 0])]
+        );
+    }
+}
@@ -54,8 +54,8 @@ pub fn web_mercator_meters_to_lnglat(mx: f64, my: f64) -> (f64, f64) {
 /// The range of (mx, my) is [0.0, 0.0]-[1.0, 1.0] (same as Mapbox/MapLibre API)
 #[inline]
 pub fn web_mercator_to_zxy(z: u8, mx: f64, my: f64) -> TileZXY {
-    let x = (mx * (1 << z) as f64) as u32;
-    let y = (my * (1 << z) as f64) as u32;
+    let x = (mx * (1u64 << z) as f64) as u32;
+    let y = (my * (1u64 << z) as f64) as u32;
     (z, x, y)
 }
 
@@ -66,6 +66,110 @@ pub fn lnglat_to_zxy(z: u8, lng: f64, lat: f64) -> TileZXY {
     web_mercator_to_zxy(z, mx, my)
 }
 
+/// Calculates the bounds of the tile in normalized Web Mercator coordinates.
+///
+/// Returns (min_mx, min_my, max_mx, max_my) in the range [0.0, 0.0]-[1.0, 1.0]. As my grows
+/// southward, (min_mx, min_my) is the north-west corner.
+#[inline]
+pub fn zxy_to_web_mercator_bounds((z, x, y): TileZXY) -> [f64; 4] {
+    let n = (1u64 << z) as f64;
+    [
+        x as f64 / n,
+        y as f64 / n,
+        (x as f64 + 1.0) / n,
+        (y as f64 + 1.0) / n,
+    ]
+}
+
+/// Calculates the bounds of the tile in Web Mercator meters.
+///
+/// Returns (min_x, min_y, max_x, max_y), where y grows northward.
+#[inline]
+pub fn zxy_to_meters_bounds(tile: TileZXY) -> [f64; 4] {
+    let [min_mx, min_my, max_mx, max_my] = zxy_to_web_mercator_bounds(tile);
+    let half = CIRCUMFERENCE / 2.0;
+    [
+        min_mx * CIRCUMFERENCE - half,
+        half - max_my * CIRCUMFERENCE,
+        max_mx * CIRCUMFERENCE - half,
+        half - min_my * CIRCUMFERENCE,
+    ]
+}
+
+/// Calculates the bounds of the tile in geographic coordinates.
+///
+/// Returns (west, south, east, north) in degrees.
+#[inline]
+pub fn zxy_to_lnglat_bounds(tile: TileZXY) -> [f64; 4] {
+    let [min_mx, min_my, max_mx, max_my] = zxy_to_web_mercator_bounds(tile);
+    let (west, north) = web_mercator_to_lnglat(min_mx, min_my);
+    let (east, south) = web_mercator_to_lnglat(max_mx, max_my);
+    [west, south, east, north]
+}
+
+/// Calculates the center of the tile in geographic coordinates (lng, lat).
+///
+/// The center is taken in Web Mercator space, so its latitude is not the midpoint of the
+/// tile's south and north bounds.
+#[inline]
+pub fn zxy_to_lnglat_center(tile: TileZXY) -> (f64, f64) {
+    let [min_mx, min_my, max_mx, max_my] = zxy_to_web_mercator_bounds(tile);
+    web_mercator_to_lnglat((min_mx + max_mx) / 2.0, (min_my + max_my) / 2.0)
+}
+
+/// Calculates the center of the tile in Web Mercator meters (mx, my).
+#[inline]
+pub fn zxy_to_meters_center(tile: TileZXY) -> (f64, f64) {
+    let [min_x, min_y, max_x, max_y] = zxy_to_meters_bounds(tile);
+    ((min_x + max_x) / 2.0, (min_y + max_y) / 2.0)
+}
+
+/// Converts tile-local coordinate (x, y) in the given tile to geographic coordinate (lng, lat).
+///
+/// The range of (x, y) within the tile is [0, 0]-[extent, extent].
+#[inline]
+pub fn tile_local_to_lnglat((z, tx, ty): TileZXY, extent: u32, x: f64, y: f64) -> (f64, f64) {
+    let scale = extent as f64 * (1u64 << z) as f64;
+    let mx = (tx as f64 * extent as f64 + x) / scale;
+    let my = (ty as f64 * extent as f64 + y) / scale;
+    web_mercator_to_lnglat(mx, my)
+}
+
+/// Converts geographic coordinate (lng, lat) to tile-local coordinate (x, y) in the given tile.
+///
+/// The range of (x, y) within the tile is [0, 0]-[extent, extent]; points outside the tile
+/// are mapped outside that range.
+#[inline]
+pub fn lnglat_to_tile_local((z, tx, ty): TileZXY, extent: u32, lng: f64, lat: f64) -> (f64, f64) {
+    let (mx, my) = lnglat_to_web_mercator(lng, lat);
+    let n = (1u64 << z) as f64;
+    let x = (mx * n - tx as f64) * extent as f64;
+    let y = (my * n - ty as f64) * extent as f64;
+    (x, y)
+}
+
+/// Converts an integer tile-local coordinate, as used in MVT geometries, to geographic coordinate (lng, lat).
+#[inline]
+pub fn tile_coord_to_lnglat(tile: TileZXY, extent: u32, [x, y]: [i32; 2]) -> (f64, f64) {
+    tile_local_to_lnglat(tile, extent, x as f64, y as f64)
+}
+
+/// Largest magnitude of an integer tile-local coordinate produced by [`lnglat_to_tile_coord`].
+///
+/// The difference between any two coordinates in this range fits in `i32`.
+pub const MAX_TILE_COORD: i32 = (1 << 30) - 1;
+
+/// Converts geographic coordinate (lng, lat) to an integer tile-local coordinate, rounded to the nearest.
+///
+/// Coordinates far outside the tile are clamped to `±MAX_TILE_COORD`, which may distort
+/// segments to such points but keeps them usable by the clipper and encoder.
+#[inline]
+pub fn lnglat_to_tile_coord(tile: TileZXY, extent: u32, lng: f64, lat: f64) -> [i32; 2] {
+    let (x, y) = lnglat_to_tile_local(tile, extent, lng, lat);
+    let limit = MAX_TILE_COORD as f64;
+    [x, y].map(|v| v.clamp(-limit, limit).round() as i32)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -150,4 +254,105 @@ mod tests {
         assert_eq!(web_mercator_to_zxy(13, 0.5, 1.), (13, 1 << 12, 1 << 13));
         assert_eq!(web_mercator_to_zxy(13, 1., 0.5), (13, 1 << 13, 1 << 12));
     }
+
+    #[test]
+    fn test_tile_local_to_lnglat() {
+        let (lng, lat) = tile_local_to_lnglat((0, 0, 0), 4096, 2048., 2048.);
+        assert!(lng.abs() < 1e-10);
+        assert!(lat.abs() < 1e-10);
+
+        let (lng, lat) = tile_local_to_lnglat((1, 1, 1), 4096, 4096., 4096.);
+        assert!((lng - 180.).abs() < 1e-10);
+        assert!((lat + 85.0511287798066).abs() < 1e-10);
+
+        let (lng, lat) = tile_local_to_lnglat((13, 7242, 3184), 4096, 0., 0.);
+        assert_eq!(lnglat_to_zxy(13, lng + 1e-9, lat - 1e-9), (13, 7242, 3184));
+    }
+
+    #[test]
+    fn roundtrip_tile_local() {
+        let tile = (13, 7242, 3184);
+        for (x, y) in [(0., 0.), (100.5, 4000.), (-64., 4160.)] {
+            let (lng, lat) = tile_local_to_lnglat(tile, 4096, x, y);
+            let (x2, y2) = lnglat_to_tile_local(tile, 4096, lng, lat);
+            assert!((x - x2).abs() < 1e-6);
+            assert!((y - y2).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_tile_bounds() {
+        let [west, south, east, north] = zxy_to_lnglat_bounds((0, 0, 0));
+        assert!((west + 180.).abs() < 1e-10);
+        assert!((south + 85.0511287798066).abs() < 1e-10);
+        assert!((east - 180.).abs() < 1e-10);
+        assert!((north - 85.0511287798066).abs() < 1e-10);
+
+        let [west, south, east, north] = zxy_to_lnglat_bounds((1, 1, 0));
+        assert!(west.abs() < 1e-10);
+        assert!(south.abs() < 1e-10);
+        assert!((east - 180.).abs() < 1e-10);
+        assert!((north - 85.0511287798066).abs() < 1e-10);
+
+        assert_eq!(
+            zxy_to_web_mercator_bounds((2, 1, 3)),
+            [0.25, 0.75, 0.5, 1.0]
+        );
+
+        let half = CIRCUMFERENCE / 2.;
+        let [min_x, min_y, max_x, max_y] = zxy_to_meters_bounds((1, 0, 1));
+        assert!((min_x + half).abs() < 1e-7);
+        assert!((min_y + half).abs() < 1e-7);
+        assert!(max_x.abs() < 1e-7);
+        assert!(max_y.abs() < 1e-7);
+
+        // consistent with lnglat_to_zxy
+        let tile = (13, 7242, 3184);
+        let [west, south, east, north] = zxy_to_lnglat_bounds(tile);
+        let (lng, lat) = zxy_to_lnglat_center(tile);
+        assert!(west < lng && lng < east && south < lat && lat < north);
+        assert_eq!(lnglat_to_zxy(13, lng, lat), tile);
+    }
+
+    #[test]
+    fn test_tile_center() {
+        let (lng, lat) = zxy_to_lnglat_center((0, 0, 0));
+        assert!(lng.abs() < 1e-10);
+        assert!(lat.abs() < 1e-10);
+
+        let (mx, my) = zxy_to_meters_center((1, 1, 0));
+        assert!((mx - CIRCUMFERENCE / 4.).abs() < 1e-7);
+        assert!((my - CIRCUMFERENCE / 4.).abs() < 1e-7);
+        let (lng, lat) = web_mercator_meters_to_lnglat(mx, my);
+        let (lng2, lat2) = zxy_to_lnglat_center((1, 1, 0));
+        assert!((lng - lng2).abs() < 1e-9);
+        assert!((lat - lat2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn roundtrip_tile_coord() {
+        let tile = (13, 7242, 3184);
+        for coord in [[0, 0], [4096, 4096], [-64, 2048], [123, 4160]] {
+            let (lng, lat) = tile_coord_to_lnglat(tile, 4096, coord);
+            assert_eq!(lnglat_to_tile_coord(tile, 4096, lng, lat), coord);
+        }
+        // far away coordinates are clamped
+        let [x, y] = lnglat_to_tile_coord((24, 0, 0), 4096, 170.0, -85.0);
+        assert_eq!((x, y), (MAX_TILE_COORD, MAX_TILE_COORD));
+        let [x, y] = lnglat_to_tile_coord((24, 1 << 23, 1 << 23), 4096, -170.0, 85.0);
+        assert_eq!((x, y), (-MAX_TILE_COORD, -MAX_TILE_COORD));
+
+        // clamped coordinates on opposite sides can be encoded without a clipper
+        let line = [
+            [-MAX_TILE_COORD, -MAX_TILE_COORD],
+            [MAX_TILE_COORD, MAX_TILE_COORD],
+        ];
+        let mut encoder = crate::geometry::GeometryEncoder::new();
+        encoder.add_linestring(line);
+        let geometry = encoder.into_vec();
+        let lines = crate::geometry::GeometryDecoder::new(&geometry)
+            .decode_linestrings()
+            .unwrap();
+        assert_eq!(lines, [line]);
+    }
 }
@@ -0,0 +1,210 @@
+//! Streaming tile writer that encodes layers directly to protobuf bytes.
+//!
+//! Features are serialized as soon as they are added, so a full [`Tile`](crate::vector_tile::Tile)
+//! never has to be held in memory. The output is byte-for-byte identical to encoding the
+//! equivalent `Tile` with prost.
+
+use prost::Message;
+
+use crate::tag::{TagsEncoder, Value};
+use crate::vector_tile::tile::{Feature, GeomType};
+
+const WIRE_VARINT: u32 = 0;
+const WIRE_LEN: u32 = 2;
+
+/// Writer for an encoded tile.
+#[derive(Default)]
+pub struct TileWriter {
+    buf: Vec<u8>,
+}
+
+impl TileWriter {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Starts a new layer (version 2) with the given name and extent.
+    ///
+    /// The layer is appended to the tile when the returned writer is finished or dropped.
+    pub fn layer(&mut self, name: &str, extent: u32) -> LayerWriter<'_> {
+        let start = self.buf.len();
+        put_bytes(&mut self.buf, 1, name.as_bytes());
+        LayerWriter {
+            buf: &mut self.buf,
+            start,
+            tags: TagsEncoder::new(),
+            version: 2,
+            extent,
+        }
+    }
+
+    /// Consumes the writer and returns the encoded tile.
+    #[inline]
+    pub fn into_vec(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+/// Writer for a single layer, created by [`TileWriter::layer`].
+///
+/// Keys and values are collected with a [`TagsEncoder`] and appended when the layer is finished.
+pub struct LayerWriter<'a> {
+    buf: &'a mut Vec<u8>,
+    start: usize,
+    tags: TagsEncoder,
+    version: u32,
+    extent: u32,
+}
+
+impl LayerWriter<'_> {
+    /// Sets the version of the layer (defaults to 2).
+    #[inline]
+    pub fn set_version(&mut self, version: u32) {
+        self.version = version;
+    }
+
+    /// Adds a key-value pair for the next feature.
+    #[inline]
+    pub fn add_tag(&mut self, key: &str, value: impl Into<Value>) {
+        self.tags.add(key, value);
+    }
+
+    /// Writes a feature with the given encoded geometry and the tags added since the previous feature.
+    ///
+    /// The geometry is typically obtained from [`GeometryEncoder::into_vec`](crate::geometry::GeometryEncoder::into_vec).
+    pub fn add_feature(&mut self, id: Option<u64>, geom_type: GeomType, geometry: Vec<u32>) {
+        let feature = Feature {
+            id,
+            tags: self.tags.take_tags(),
+            r#type: Some(geom_type as i32),
+            geometry,
+        };
+        put_key(self.buf, 2, WIRE_LEN);
+        feature.encode_length_delimited(self.buf).unwrap();
+    }
+
+    /// Finishes the layer and appends it to the tile.
+    ///
+    /// This is equivalent to dropping the writer.
+    #[inline]
+    pub fn finish(self) {}
+
+    fn finish_inner(&mut self) {
+        let (keys, values) = std::mem::take(&mut self.tags).into_keys_and_values();
+        for key in keys {
+            put_bytes(self.buf, 3, key.as_bytes());
+        }
+        for value in values {
+            put_key(self.buf, 4, WIRE_LEN);
+            value.encode_length_delimited(self.buf).unwrap();
+        }
+        put_key(self.buf, 5, WIRE_VARINT);
+        put_varint(self.buf, self.extent as u64);
+        put_key(self.buf, 15, WIRE_VARINT);
+        put_varint(self.buf, self.version as u64);
+
+        // prepend the header of the `layers` field now that the length is known
+        let len = self.buf.len() - self.start;
+        let mut header = Vec::with_capacity(6);
+        put_key(&mut header, 3, WIRE_LEN);
+        put_varint(&mut header, len as u64);
+        self.buf.splice(self.start..self.start, header);
+    }
+}
+
+impl Drop for LayerWriter<'_> {
+    fn drop(&mut self) {
+        self.finish_inner();
+    }
+}
+
+#[inline]
+fn put_varint(buf: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        buf.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    buf.push(v as u8);
+}
+
+#[inline]
+fn put_key(buf: &mut Vec<u8>, field: u32, wire_type: u32) {
+    put_varint(buf, (field << 3 | wire_type) as u64);
+}
+
+#[inline]
+fn put_bytes(buf: &mut Vec<u8>, field: u32, bytes: &[u8]) {
+    put_key(buf, field, WIRE_LEN);
+    put_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::GeometryEncoder;
+    use crate::vector_tile::{Tile, tile};
+
+    #[test]
+    fn test_matches_prost_encoding() {
+        let mut writer = TileWriter::new();
+        {
+            let mut layer = writer.layer("points", 4096);
+            for i in 0..200 {
+                let mut geom_enc = GeometryEncoder::new();
+                geom_enc.add_points([[i, i * 2]]);
+                layer.add_tag("index", i);
+                layer.add_tag("name", format!("point-{}", i % 3));
+                layer.add_feature(Some(i as u64), GeomType::Point, geom_enc.into_vec());
+            }
+            layer.finish();
+        }
+        {
+            let mut layer = writer.layer("empty", 512);
+            layer.set_version(1);
+        }
+        let buf = writer.into_vec();
+
+        let decoded = Tile::decode(&buf[..]).unwrap();
+        assert_eq!(decoded.layers.len(), 2);
+        assert_eq!(decoded.layers[0].name, "points");
+        assert_eq!(decoded.layers[0].features.len(), 200);
+        assert_eq!(decoded.layers[0].keys, ["index", "name"]);
+        assert_eq!(decoded.layers[0].values.len(), 203);
+        assert_eq!(decoded.layers[1].name, "empty");
+        assert_eq!(decoded.layers[1].version, 1);
+        assert_eq!(decoded.layers[1].extent, Some(512));
+
+        // re-encoding with prost gives the same bytes
+        assert_eq!(decoded.encode_to_vec(), buf);
+    }
+
+    #[test]
+    fn test_empty_tile() {
+        let buf = TileWriter::new().into_vec();
+        assert!(buf.is_empty());
+        assert_eq!(Tile::decode(&buf[..]).unwrap(), Tile { layers: vec![] });
+    }
+
+    #[test]
+    fn test_feature_without_tags() {
+        let mut writer = TileWriter::new();
+        writer.layer("lines", 4096).add_feature(
+            None,
+            GeomType::Linestring,
+            vec![9, 0, 0, 10, 2, 2],
+        );
+        let buf = writer.into_vec();
+
+        let decoded = Tile::decode(&buf[..]).unwrap();
+        assert_eq!(
+            decoded.layers[0].features,
+            [tile::Feature {
+                id: None,
+                tags: vec![],
+                r#type: Some(GeomType::Linestring as i32),
+                geometry: vec![9, 0, 0, 10, 2, 2],
+            }]
+        );
+    }
+}
@@ -3,6 +3,7 @@ use std::fs;
 use prost::Message;
 use tinymvt::geometry::GeometryEncoder;
 use tinymvt::tag::{TagsEncoder, Value};
+use tinymvt::writer::TileWriter;
 use tinymvt::vector_tile::{
     tile::{Feature, GeomType, Layer},
     Tile,
@@ -118,3 +119,70 @@ fn make_tile() {
 
     assert_eq!(buf, expected);
 }
+
+#[test]
+fn write_tile() {
+    let extent = 4096;
+
+    let mut writer = TileWriter::new();
+    {
+        let mut layer = writer.layer("road", extent as u32);
+
+        let mut geom_enc = GeometryEncoder::new();
+        geom_enc.add_points([
+            [300, 300],
+            [300, extent - 300],
+            [extent - 300, extent - 300],
+            [extent - 300, 300],
+            [900, 900],
+            [900, extent - 900],
+            [extent - 900, extent - 900],
+            [extent - 900, 900],
+        ]);
+        layer.add_tag("foo", 10);
+        layer.add_tag("bar", 20.5);
+        layer.add_feature(Some(1), GeomType::Point, geom_enc.into_vec());
+
+        let mut geom_enc = GeometryEncoder::new();
+        geom_enc.add_linestring([[0, 0], [0, extent], [extent, extent], [extent, 0]]);
+        geom_enc.add_linestring([
+            [500, 500],
+            [500, extent - 500],
+            [extent - 500, extent - 500],
+            [extent - 500, 500],
+        ]);
+        geom_enc.add_linestring([
+            [700, 700],
+            [700, extent - 700],
+            [extent - 700, extent - 700],
+            [extent - 700, 700],
+        ]);
+        layer.add_tag("uint", Value::Uint(10));
+        layer.add_tag("sint", Value::SInt(-10));
+        layer.add_tag("int", Value::Int(10));
+        layer.add_tag("string", Value::String("string".to_string()));
+        layer.add_tag("float", 10.5f32);
+        layer.add_tag("double", 10.5f64);
+        layer.add_tag("bool", Value::Bool(true));
+        layer.add_feature(Some(2), GeomType::Linestring, geom_enc.into_vec());
+
+        let mut geom_enc = GeometryEncoder::new();
+        geom_enc.add_ring([[1000, 1000], [1000, 1500], [1500, 1500], [1500, 1000]]);
+        geom_enc.add_ring([[1100, 1100], [1200, 1100], [1200, 1200], [1100, 1200]]);
+        geom_enc.add_ring([[1200, 1200], [1300, 1200], [1300, 1300], [1200, 1300]]);
+        geom_enc.add_ring([[2000, 2000], [2000, 2500], [2500, 2500], [2500, 2000]]);
+        geom_enc.add_ring([[2100, 2100], [2200, 2100], [2200, 2200], [2100, 2200]]);
+        geom_enc.add_ring([[2200, 2200], [2300, 2200], [2300, 2300], [2200, 2300]]);
+        geom_enc.add_ring([[2300, 2300], [2400, 2300], [2400, 2400], [2300, 2400]]);
+        layer.add_tag("fizz", 10);
+        layer.add_tag("buzz", 20.5);
+        layer.add_feature(Some(3), GeomType::Polygon, geom_enc.into_vec());
+
+        layer.finish();
+    }
+    let buf = writer.into_vec();
+
+    let expected = fs::read("tests/fixtures/make_tile.pbf").unwrap();
+
+    assert_eq!(buf, expected);
+}